@@ -0,0 +1,101 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// The attributes of a user created or updated with the admin API. Attributes that aren't set
+/// are left out of the request, so updates only change what is set.
+///
+/// # Example
+///
+/// ```
+/// use go_true::AdminUserAttributes;
+/// use serde_json::json;
+///
+/// let user = AdminUserAttributes::new()
+///     .email("email@example.com")
+///     .password("Abcd1234!")
+///     .email_confirm(true)
+///     .user_metadata(json!({ "name": "Jane" }))
+///     .ban_duration("24h");
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AdminUserAttributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email_confirm: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone_confirm: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_metadata: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    app_metadata: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ban_duration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+}
+
+impl AdminUserAttributes {
+    pub fn new() -> AdminUserAttributes {
+        AdminUserAttributes::default()
+    }
+
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    pub fn phone(mut self, phone: impl Into<String>) -> Self {
+        self.phone = Some(phone.into());
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Marks the email address as confirmed, so no confirmation email is sent.
+    pub fn email_confirm(mut self, email_confirm: bool) -> Self {
+        self.email_confirm = Some(email_confirm);
+        self
+    }
+
+    /// Marks the phone number as confirmed, so no confirmation SMS is sent.
+    pub fn phone_confirm(mut self, phone_confirm: bool) -> Self {
+        self.phone_confirm = Some(phone_confirm);
+        self
+    }
+
+    pub fn user_metadata(mut self, user_metadata: Value) -> Self {
+        self.user_metadata = Some(user_metadata);
+        self
+    }
+
+    pub fn app_metadata(mut self, app_metadata: Value) -> Self {
+        self.app_metadata = Some(app_metadata);
+        self
+    }
+
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// Bans the user for a duration like `24h`, `none` lifts the ban.
+    pub fn ban_duration(mut self, ban_duration: impl Into<String>) -> Self {
+        self.ban_duration = Some(ban_duration.into());
+        self
+    }
+
+    pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+}
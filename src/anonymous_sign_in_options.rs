@@ -0,0 +1,9 @@
+use serde_json::Value;
+
+/// Optional parameters for an anonymous sign in.
+#[derive(Debug, Clone, Default)]
+pub struct AnonymousSignInOptions {
+    /// Stored as the anonymous user's `user_metadata`.
+    pub data: Option<Value>,
+    pub captcha_token: Option<String>,
+}
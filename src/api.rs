@@ -1,9 +1,34 @@
+use futures::{stream, Stream, TryStreamExt};
 use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName};
+use serde::Deserialize;
 use serde_json::json;
 
 use crate::{
-    session::Session, user::User, user_attributes::UserAttributes, user_list::UserList,
+    admin_user_attributes::AdminUserAttributes,
+    anonymous_sign_in_options::AnonymousSignInOptions,
+    authorize_url_builder::AuthorizeUrlBuilder,
+    channel::Channel,
+    email_change_options::EmailChangeOptions,
+    email_change_outcome::EmailChangeOutcome,
+    email_change_request::EmailChangeRequest,
+    health::Health,
+    id_token_credentials::IdTokenCredentials,
+    mfa::{Challenge, EnrollParams, EnrollResponse, Factor},
+    oauth_options::OAuthOptions,
+    pkce::{Pkce, CODE_CHALLENGE_METHOD},
+    provider::Provider,
+    resend_options::{ResendOptions, ResendType},
+    reset_password_options::ResetPasswordOptions,
+    session::Session,
+    settings::Settings,
+    sign_out_scope::SignOutScope,
+    sign_up_options::SignUpOptions,
+    sign_up_outcome::SignUpOutcome,
+    user::User,
+    user_attributes::UserAttributes,
+    user_list::{parse_link_header, ListUsersOptions, UserList},
     user_update::UserUpdate,
+    verify_otp_params::{EmailOtpType, MobileOtpType, VerifyOtpParams},
 };
 
 pub struct Api {
@@ -17,6 +42,11 @@ pub enum EmailOrPhone {
     Phone(String),
 }
 
+pub enum DomainOrProviderId {
+    Domain(String),
+    ProviderId(String),
+}
+
 impl Api {
     /// Creates a GoTrue API client.
     ///
@@ -58,7 +88,8 @@ impl Api {
         self
     }
 
-    /// Signs up for a new account
+    /// Signs up for a new account. Depending on the GoTrue configuration the user either gets
+    /// a session right away or has to confirm their email address or phone number first.
     ///
     /// # Example
     ///
@@ -72,7 +103,7 @@ impl Api {
     ///     let email = "email@example.com".to_string();
     ///     let password = "Abcd1234!";
     ///
-    ///     let result = client.sign_up(EmailOrPhone::Email(email), password).await;
+    ///     let result = client.sign_up(EmailOrPhone::Email(email), password, None).await;
     ///     Ok(())
     /// }
     /// ```
@@ -80,32 +111,39 @@ impl Api {
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str>,
-    ) -> Result<Session, reqwest::Error> {
+        options: Option<SignUpOptions>,
+    ) -> Result<SignUpOutcome, reqwest::Error> {
         let endpoint = format!("{}/signup", self.url);
+        let options = options.unwrap_or_default();
 
-        let body = match email_or_phone {
+        let mut body = match email_or_phone {
             EmailOrPhone::Email(email) => json!({
                 "email": email,
                 "password": password.as_ref(),
             }),
             EmailOrPhone::Phone(phone) => json!({
                 "phone": phone,
-                "password": password.as_ref()
+                "password": password.as_ref(),
+                "channel": options.channel,
             }),
         };
+        body["data"] = json!(options.data);
+        body["gotrue_meta_security"] = json!({ "captcha_token": options.captcha_token });
 
-        let response: Session = self
-            .client
-            .post(endpoint)
-            .headers(self.headers.clone())
+        let mut request = self.client.post(endpoint).headers(self.headers.clone());
+        if let Some(redirect_to) = options.email_redirect_to {
+            request = request.query(&[("redirect_to", redirect_to)]);
+        }
+
+        let response: SignUpOutcome = request
             .json(&body)
             .send()
             .await?
             .error_for_status()?
-            .json::<Session>()
+            .json::<SignUpOutcome>()
             .await?;
 
-        return Ok(response);
+        Ok(response)
     }
 
     /// Signs into an existing account
@@ -147,21 +185,992 @@ impl Api {
             }),
         };
 
-        let response: Session = self
+        let response: Session = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Session>()
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Signs in with an ID token issued by an OIDC provider
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, IdTokenCredentials, Provider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let credentials = IdTokenCredentials {
+    ///         provider: Provider::Google,
+    ///         id_token: "id_token_from_google".to_string(),
+    ///         access_token: None,
+    ///         nonce: None,
+    ///     };
+    ///
+    ///     let result = client.sign_in_with_id_token(credentials).await;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> Result<Session, reqwest::Error> {
+        let endpoint = format!("{}/token?grant_type=id_token", self.url);
+
+        let body = json!({
+            "provider": credentials.provider.as_str(),
+            "id_token": credentials.id_token,
+            "access_token": credentials.access_token,
+            "nonce": credentials.nonce,
+        });
+
+        let session: Session = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Signs in as a new anonymous user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::Api;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let result = client.sign_in_anonymously(None).await;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sign_in_anonymously(
+        &self,
+        options: Option<AnonymousSignInOptions>,
+    ) -> Result<Session, reqwest::Error> {
+        let endpoint = format!("{}/signup", self.url);
+        let options = options.unwrap_or_default();
+
+        let body = json!({
+            "data": options.data,
+            "gotrue_meta_security": { "captcha_token": options.captcha_token },
+        });
+
+        let session: Session = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Sends an OTP Code and creates user if it does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///
+    ///     let result = client.send_otp(EmailOrPhone::Email(email), None).await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn send_otp(
+        &self,
+        email_or_phone: EmailOrPhone,
+        should_create_user: Option<bool>,
+    ) -> Result<bool, reqwest::Error> {
+        let endpoint = format!("{}/otp", self.url);
+
+        let body = match email_or_phone {
+            EmailOrPhone::Email(email) => json!({
+                "email": email,
+                "should_create_user": Some(should_create_user)
+            }),
+            EmailOrPhone::Phone(phone) => json!({
+                "phone": phone,
+                "should_create_user": Some(should_create_user)
+            }),
+        };
+
+        self.client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(true)
+    }
+
+    /// Resends a signup confirmation, email change or phone OTP
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone, ResendType};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///
+    ///     let result = client
+    ///         .resend(ResendType::Signup, EmailOrPhone::Email(email), None)
+    ///         .await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn resend(
+        &self,
+        resend_type: ResendType,
+        email_or_phone: EmailOrPhone,
+        options: Option<ResendOptions>,
+    ) -> Result<bool, reqwest::Error> {
+        let endpoint = format!("{}/resend", self.url);
+        let options = options.unwrap_or_default();
+
+        let body = match email_or_phone {
+            EmailOrPhone::Email(email) => json!({
+                "type": resend_type,
+                "email": email,
+                "gotrue_meta_security": { "captcha_token": options.captcha_token },
+            }),
+            EmailOrPhone::Phone(phone) => json!({
+                "type": resend_type,
+                "phone": phone,
+                "gotrue_meta_security": { "captcha_token": options.captcha_token },
+            }),
+        };
+
+        let mut request = self.client.post(endpoint).headers(self.headers.clone());
+        if let Some(redirect_to) = options.email_redirect_to {
+            request = request.query(&[("redirect_to", redirect_to)]);
+        }
+
+        request.json(&body).send().await?.error_for_status()?;
+
+        Ok(true)
+    }
+
+    /// Verifies an OTP and returns the session issued for it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, MobileOtpType, VerifyOtpParams};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let params = VerifyOtpParams::Mobile {
+    ///         phone: "+4912345678".to_string(),
+    ///         token: "123456".to_string(),
+    ///         otp_type: MobileOtpType::Sms,
+    ///     };
+    ///
+    ///     let session = client.verify_otp(params).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn verify_otp(&self, params: VerifyOtpParams) -> Result<Session, reqwest::Error> {
+        let endpoint = format!("{}/verify", self.url);
+
+        let session: Session = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Signs the current user out. Without a scope GoTrue revokes all sessions of the user.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone, SignOutScope};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     client.sign_out(&session.access_token, Some(SignOutScope::Local));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sign_out(
+        &self,
+        access_token: impl AsRef<str>,
+        scope: Option<SignOutScope>,
+    ) -> Result<bool, reqwest::Error> {
+        let endpoint = format!("{}/logout", self.url);
+
+        let headers = self.authorized_headers(access_token.as_ref());
+
+        let mut request = self.client.post(endpoint).headers(headers);
+
+        if let Some(scope) = scope {
+            request = request.query(&[("scope", scope)]);
+        }
+
+        request.send().await?.error_for_status()?;
+
+        Ok(true)
+    }
+
+    /// Sends password recovery email
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, Pkce, ResetPasswordOptions};
+    ///
+    /// let mut client = Api::new("http://localhost:9998");
+    /// let email = "random@mail.com";
+    /// let pkce = Pkce::generate();
+    ///
+    /// let options = ResetPasswordOptions {
+    ///     redirect_to: Some("http://localhost:3000/reset".to_string()),
+    ///     code_challenge: Some(pkce.code_challenge),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// client.reset_password_for_email(email, Some(options));
+    /// ```
+    pub async fn reset_password_for_email(
+        &self,
+        email: impl AsRef<str>,
+        options: Option<ResetPasswordOptions>,
+    ) -> Result<bool, reqwest::Error> {
+        let endpoint = format!("{}/recover", self.url);
+        let options = options.unwrap_or_default();
+
+        let code_challenge_method = options
+            .code_challenge
+            .as_ref()
+            .map(|_| CODE_CHALLENGE_METHOD);
+
+        let body = json!({
+            "email": email.as_ref(),
+            "code_challenge": options.code_challenge,
+            "code_challenge_method": code_challenge_method,
+            "gotrue_meta_security": { "captcha_token": options.captcha_token },
+        });
+
+        let mut request = self.client.post(endpoint).headers(self.headers.clone());
+        if let Some(redirect_to) = options.redirect_to {
+            request = request.query(&[("redirect_to", redirect_to)]);
+        }
+
+        request.json(&body).send().await?.error_for_status()?;
+
+        Ok(true)
+    }
+
+    /// Gets the authorize url for a provider
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, Provider};
+    ///
+    /// let client = Api::new("http://localhost:9998");
+    /// let url = client.get_url_for_provider(Provider::Github);
+    /// ```
+    pub fn get_url_for_provider(&self, provider: Provider) -> String {
+        self.authorize_url(provider).build()
+    }
+
+    /// Gets the authorize url for a provider using the PKCE flow. The returned code verifier
+    /// has to be kept until the auth code is exchanged with `exchange_code_for_session`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, Provider};
+    ///
+    /// let client = Api::new("http://localhost:9998");
+    /// let (url, pkce) = client.get_url_for_provider_with_pkce(Provider::Github);
+    /// ```
+    pub fn get_url_for_provider_with_pkce(&self, provider: Provider) -> (String, Pkce) {
+        let pkce = Pkce::generate();
+        let url = self.authorize_url(provider).pkce(&pkce).build();
+
+        (url, pkce)
+    }
+
+    /// Starts building the authorize url for a provider with redirect, scopes, query
+    /// parameters or PKCE
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, Provider};
+    ///
+    /// let client = Api::new("http://localhost:9998");
+    /// let url = client
+    ///     .authorize_url(Provider::Github)
+    ///     .redirect_to("https://your.app/callback")
+    ///     .scopes("repo gist")
+    ///     .build();
+    /// ```
+    pub fn authorize_url(&self, provider: Provider) -> AuthorizeUrlBuilder {
+        AuthorizeUrlBuilder::new(&self.url, provider)
+    }
+
+    /// Exchanges an auth code from the PKCE flow for a session
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, Provider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let (url, pkce) = client.get_url_for_provider_with_pkce(Provider::Github);
+    ///
+    ///     // redirect the user to `url` and read the `code` query parameter from the callback
+    ///     let auth_code = "auth_code";
+    ///
+    ///     let session = client
+    ///         .exchange_code_for_session(auth_code, &pkce.code_verifier)
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn exchange_code_for_session(
+        &self,
+        auth_code: impl AsRef<str>,
+        code_verifier: impl AsRef<str>,
+    ) -> Result<Session, reqwest::Error> {
+        let endpoint = format!("{}/token?grant_type=pkce", self.url);
+
+        let body = json!({
+            "auth_code": auth_code.as_ref(),
+            "code_verifier": code_verifier.as_ref(),
+        });
+
+        let session: Session = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Gets the url of the SAML identity provider for enterprise SSO. The user has to be
+    /// redirected there to sign in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, DomainOrProviderId};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let domain = DomainOrProviderId::Domain("company.com".to_string());
+    ///     let redirect_to = Some("https://your.app/welcome".to_string());
+    ///
+    ///     let url = client.sign_in_with_sso(domain, redirect_to).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sign_in_with_sso(
+        &self,
+        domain_or_provider_id: DomainOrProviderId,
+        redirect_to: Option<String>,
+    ) -> Result<String, reqwest::Error> {
+        #[derive(Deserialize)]
+        struct SsoResponse {
+            url: String,
+        }
+
+        let endpoint = format!("{}/sso", self.url);
+
+        let body = match domain_or_provider_id {
+            DomainOrProviderId::Domain(domain) => json!({
+                "domain": domain,
+                "redirect_to": redirect_to,
+                "skip_http_redirect": true,
+            }),
+            DomainOrProviderId::ProviderId(provider_id) => json!({
+                "provider_id": provider_id,
+                "redirect_to": redirect_to,
+                "skip_http_redirect": true,
+            }),
+        };
+
+        let response: SsoResponse = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.url)
+    }
+
+    /// Refreshes the current session by refresh token
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     client.refresh_access_token(&session.refresh_token);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn refresh_access_token(
+        &self,
+        refresh_token: impl AsRef<str>,
+    ) -> Result<Session, reqwest::Error> {
+        let endpoint = format!("{}/token?grant_type=refresh_token", self.url);
+        let body = json!({ "refresh_token": refresh_token.as_ref() });
+
+        let session: Session = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Checks the health of the GoTrue server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let health = client.health().await?;
+    ///     println!("{} {}", health.name, health.version);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn health(&self) -> Result<Health, reqwest::Error> {
+        let endpoint = format!("{}/health", self.url);
+
+        let health: Health = self
+            .client
+            .get(endpoint)
+            .headers(self.headers.clone())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(health)
+    }
+
+    /// Gets the settings of the GoTrue server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let settings = client.get_settings().await?;
+    ///     let github_enabled = settings.external.get("github") == Some(&true);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_settings(&self) -> Result<Settings, reqwest::Error> {
+        let endpoint = format!("{}/settings", self.url);
+
+        let settings: Settings = self
+            .client
+            .get(endpoint)
+            .headers(self.headers.clone())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(settings)
+    }
+
+    /// Gets a user by access token
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let user = client.get_user(&session.access_token);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_user(&self, jwt: impl AsRef<str>) -> Result<User, reqwest::Error> {
+        let endpoint = format!("{}/user", self.url);
+
+        let headers = self.authorized_headers(jwt.as_ref());
+
+        let user: User = self
+            .client
+            .get(endpoint)
+            .headers(headers)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Updates a user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone, UserAttributes};
+    /// use serde_json::json;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     client.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+    ///         .await?;
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///
+    ///     let new_email = "otheremail@example.com";
+    ///     let attributes = UserAttributes {
+    ///         email: Some(new_email.to_string()),
+    ///         password: Some("Abcd12345!".to_string()),
+    ///         data: Some(json!({ "test": "test" })),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let updated_user = client.update_user(attributes, &session.access_token).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn update_user(
+        &self,
+        user: UserAttributes,
+        jwt: impl AsRef<str>,
+    ) -> Result<UserUpdate, reqwest::Error> {
+        let endpoint = format!("{}/user", self.url);
+
+        let headers = self.authorized_headers(jwt.as_ref());
+
+        let body = json!({
+            "email": user.email,
+            "phone": user.phone,
+            "password": user.password,
+            "data": user.data,
+            "nonce": user.nonce,
+        });
+
+        let user: UserUpdate = self
+            .client
+            .put(endpoint)
+            .headers(headers)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<UserUpdate>()
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Requests to change the email address of a user. GoTrue sends a confirmation to the new
+    /// address, and also to the current one if secure email change is enabled. The returned
+    /// request tells which addresses still have to confirm, as far as the options allow.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailChangeOptions, EmailOrPhone};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let options = EmailChangeOptions {
+    ///         email_redirect_to: Some("http://localhost:3000/email-changed".to_string()),
+    ///         secure_email_change: Some(true),
+    ///     };
+    ///     let request = client
+    ///         .request_email_change(&session.access_token, "new@example.com", Some(options))
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn request_email_change(
+        &self,
+        jwt: impl AsRef<str>,
+        new_email: impl AsRef<str>,
+        options: Option<EmailChangeOptions>,
+    ) -> Result<EmailChangeRequest, reqwest::Error> {
+        let options = options.unwrap_or_default();
+        let endpoint = format!("{}/user", self.url);
+
+        let headers = self.authorized_headers(jwt.as_ref());
+
+        let body = json!({
+            "email": new_email.as_ref(),
+        });
+
+        let mut request = self.client.put(endpoint).headers(headers);
+        if let Some(redirect_to) = options.email_redirect_to {
+            request = request.query(&[("redirect_to", redirect_to)]);
+        }
+
+        let update: UserUpdate = request
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(EmailChangeRequest::new(update, options.secure_email_change))
+    }
+
+    /// Verifies a token of an email change, sent to either the current or the new address
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailChangeOutcome};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let outcome = client
+    ///         .verify_email_change("new@example.com", "123456")
+    ///         .await?;
+    ///
+    ///     if let EmailChangeOutcome::OtherAddressPending { msg } = outcome {
+    ///         println!("{}", msg);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn verify_email_change(
+        &self,
+        email: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Result<EmailChangeOutcome, reqwest::Error> {
+        let endpoint = format!("{}/verify", self.url);
+
+        let params = VerifyOtpParams::Email {
+            email: email.into(),
+            token: token.into(),
+            otp_type: EmailOtpType::EmailChange,
+        };
+
+        let outcome: EmailChangeOutcome = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .json(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(outcome)
+    }
+
+    /// Requests to change the phone number of a user. GoTrue sends an OTP to the new number,
+    /// which is verified with `verify_phone_change`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let update = client
+    ///         .request_phone_change(&session.access_token, "+4912345678", None)
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn request_phone_change(
+        &self,
+        jwt: impl AsRef<str>,
+        new_phone: impl AsRef<str>,
+        channel: Option<Channel>,
+    ) -> Result<UserUpdate, reqwest::Error> {
+        let endpoint = format!("{}/user", self.url);
+
+        let headers = self.authorized_headers(jwt.as_ref());
+
+        let body = json!({
+            "phone": new_phone.as_ref(),
+            "channel": channel,
+        });
+
+        let update: UserUpdate = self
+            .client
+            .put(endpoint)
+            .headers(headers)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(update)
+    }
+
+    /// Verifies the OTP sent to the new phone number and returns the session issued for it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let session = client.verify_phone_change("+4912345678", "123456").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn verify_phone_change(
+        &self,
+        phone: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Result<Session, reqwest::Error> {
+        self.verify_otp(VerifyOtpParams::Mobile {
+            phone: phone.into(),
+            token: token.into(),
+            otp_type: MobileOtpType::PhoneChange,
+        })
+        .await
+    }
+
+    /// Sends a reauthentication nonce to the user's email address or phone number. The nonce
+    /// has to be passed along when updating the password with secure password change enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     client.reauthenticate(&session.access_token).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn reauthenticate(&self, jwt: impl AsRef<str>) -> Result<bool, reqwest::Error> {
+        let endpoint = format!("{}/reauthenticate", self.url);
+
+        let headers = self.authorized_headers(jwt.as_ref());
+
+        self.client
+            .get(endpoint)
+            .headers(headers)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(true)
+    }
+
+    /// Gets the url for linking an OAuth identity to the user. The user has to be redirected
+    /// there to sign in with the provider.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Api, EmailOrPhone, Provider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let url = client
+    ///         .link_identity(&session.access_token, Provider::Github, None)
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn link_identity(
+        &self,
+        jwt: impl AsRef<str>,
+        provider: Provider,
+        options: Option<OAuthOptions>,
+    ) -> Result<String, reqwest::Error> {
+        #[derive(Deserialize)]
+        struct LinkIdentityResponse {
+            url: String,
+        }
+
+        let endpoint = format!("{}/user/identities/authorize", self.url);
+        let options = options.unwrap_or_default();
+
+        let mut query = vec![
+            ("provider", provider.as_str()),
+            ("skip_http_redirect", "true"),
+        ];
+        if let Some(redirect_to) = &options.redirect_to {
+            query.push(("redirect_to", redirect_to));
+        }
+        if let Some(scopes) = &options.scopes {
+            query.push(("scopes", scopes));
+        }
+        for (key, value) in &options.query_params {
+            query.push((key, value));
+        }
+
+        let response: LinkIdentityResponse = self
             .client
-            .post(endpoint)
-            .headers(self.headers.clone())
-            .json(&body)
+            .get(endpoint)
+            .headers(self.authorized_headers(jwt.as_ref()))
+            .query(&query)
             .send()
             .await?
             .error_for_status()?
-            .json::<Session>()
+            .json()
             .await?;
 
-        return Ok(response);
+        Ok(response.url)
     }
 
-    /// Sends an OTP Code and creates user if it does not exist
+    /// Unlinks an identity from the user
     ///
     /// # Example
     ///
@@ -170,137 +1179,153 @@ impl Api {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Api::new("http://localhost:9998");
+    ///     let client = Api::new("http://localhost:9998");
     ///
     ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let identities = session.user.identities.unwrap_or_default();
+    ///
+    ///     client
+    ///         .unlink_identity(&session.access_token, &identities[0].identity_id)
+    ///         .await?;
     ///
-    ///     let result = client.send_otp(EmailOrPhone::Email(email), None).await;
     ///     Ok(())
     /// }
     /// ```
-    pub async fn send_otp(
+    pub async fn unlink_identity(
         &self,
-        email_or_phone: EmailOrPhone,
-        should_create_user: Option<bool>,
+        jwt: impl AsRef<str>,
+        identity_id: impl AsRef<str>,
     ) -> Result<bool, reqwest::Error> {
-        let endpoint = format!("{}/otp", self.url);
-
-        let body = match email_or_phone {
-            EmailOrPhone::Email(email) => json!({
-                "email": email,
-                "should_create_user": Some(should_create_user)
-            }),
-            EmailOrPhone::Phone(phone) => json!({
-                "phone": phone,
-                "should_create_user": Some(should_create_user)
-            }),
-        };
-
-        self.client
-            .post(endpoint)
-            .headers(self.headers.clone())
-            .json(&body)
-            .send()
-            .await?
-            .error_for_status()?;
-
-        return Ok(true);
-    }
-
-    pub async fn verify_otp<T: serde::Serialize>(&self, params: T) -> Result<bool, reqwest::Error> {
-        let endpoint = format!("{}/verify", self.url);
-
-        let body = serde_json::to_value(&params).unwrap();
+        let endpoint = format!("{}/user/identities/{}", self.url, identity_id.as_ref());
 
         self.client
-            .post(endpoint)
-            .headers(self.headers.clone())
-            .json(&body)
+            .delete(endpoint)
+            .headers(self.authorized_headers(jwt.as_ref()))
             .send()
             .await?
             .error_for_status()?;
 
-        return Ok(true);
+        Ok(true)
     }
 
-    /// Signs the current user out
+    /// Enrolls a new factor for the user. The factor stays unverified until the first
+    /// successful challenge.
     ///
     /// # Example
     ///
     /// ```
-    /// use go_true::{Api, EmailOrPhone};
+    /// use go_true::{mfa::EnrollParams, Api, EmailOrPhone};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Api::new("http://localhost:9998");
+    ///     let client = Api::new("http://localhost:9998");
     ///
     ///     let email = "email@example.com".to_string();
     ///     let password = "Abcd1234!";
     ///
     ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
-    ///     client.sign_out(&session.access_token);
+    ///     let params = EnrollParams::Totp {
+    ///         friendly_name: Some("Phone".to_string()),
+    ///         issuer: None,
+    ///     };
+    ///
+    ///     let factor = client.mfa_enroll(&session.access_token, params).await?;
+    ///     println!("{}", factor.totp.unwrap().qr_code);
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn sign_out(&self, access_token: impl AsRef<str>) -> Result<bool, reqwest::Error> {
-        let endpoint = format!("{}/logout", self.url);
-
-        let mut headers: HeaderMap = self.headers.clone();
-        let bearer = format!("Bearer {}", access_token.as_ref());
-        headers.insert(
-            "Authorization",
-            HeaderValue::from_str(bearer.as_ref()).expect("Invalid header value."),
-        );
+    pub async fn mfa_enroll(
+        &self,
+        jwt: impl AsRef<str>,
+        params: EnrollParams,
+    ) -> Result<EnrollResponse, reqwest::Error> {
+        let endpoint = format!("{}/factors", self.url);
+
+        let body = match params {
+            EnrollParams::Totp {
+                friendly_name,
+                issuer,
+            } => json!({
+                "factor_type": "totp",
+                "friendly_name": friendly_name,
+                "issuer": issuer,
+            }),
+            EnrollParams::Phone {
+                friendly_name,
+                phone,
+            } => json!({
+                "factor_type": "phone",
+                "friendly_name": friendly_name,
+                "phone": phone,
+            }),
+        };
 
-        self.client
+        let response: EnrollResponse = self
+            .client
             .post(endpoint)
-            .headers(headers)
+            .headers(self.authorized_headers(jwt.as_ref()))
+            .json(&body)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_status()?
+            .json()
+            .await?;
 
-        return Ok(true);
+        Ok(response)
     }
 
-    /// Sends password recovery email
+    /// Creates a challenge for a factor. For phone factors the code is sent via the given
+    /// channel, which defaults to SMS.
     ///
     /// # Example
     ///
     /// ```
     /// use go_true::{Api, EmailOrPhone};
     ///
-    /// let mut client = Api::new("http://localhost:9998");
-    /// let email = "random@mail.com";
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let email = "email@example.com".to_string();
+    ///     let password = "Abcd1234!";
+    ///
+    ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let challenge = client
+    ///         .mfa_challenge(&session.access_token, "factor_id", None)
+    ///         .await?;
     ///
-    /// client.reset_password_for_email(email);
+    ///     Ok(())
+    /// }
     /// ```
-    pub async fn reset_password_for_email(
+    pub async fn mfa_challenge(
         &self,
-        email: impl AsRef<str>,
-    ) -> Result<bool, reqwest::Error> {
-        let endpoint = format!("{}/recover", self.url);
+        jwt: impl AsRef<str>,
+        factor_id: impl AsRef<str>,
+        channel: Option<Channel>,
+    ) -> Result<Challenge, reqwest::Error> {
+        let endpoint = format!("{}/factors/{}/challenge", self.url, factor_id.as_ref());
 
-        let body = json!({
-            "email": email.as_ref(),
-        });
+        let body = json!({ "channel": channel });
 
-        self.client
+        let challenge: Challenge = self
+            .client
             .post(endpoint)
-            .headers(self.headers.clone())
+            .headers(self.authorized_headers(jwt.as_ref()))
             .json(&body)
             .send()
             .await?
-            .error_for_status()?;
-
-        return Ok(true);
-    }
+            .error_for_status()?
+            .json()
+            .await?;
 
-    pub fn get_url_for_provider(&self, provider: &str) -> String {
-        return format!("{}/authorize?provider={}", self.url, provider);
+        Ok(challenge)
     }
 
-    /// Refreshes the current session by refresh token
+    /// Verifies a challenge with the code of the factor and returns the upgraded session
     ///
     /// # Example
     ///
@@ -309,28 +1334,40 @@ impl Api {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Api::new("http://localhost:9998");
+    ///     let client = Api::new("http://localhost:9998");
     ///
     ///     let email = "email@example.com".to_string();
     ///     let password = "Abcd1234!";
     ///
     ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
-    ///     client.refresh_access_token(&session.refresh_token);
+    ///     let challenge = client
+    ///         .mfa_challenge(&session.access_token, "factor_id", None)
+    ///         .await?;
+    ///     let session = client
+    ///         .mfa_verify(&session.access_token, "factor_id", &challenge.id, "123456")
+    ///         .await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn refresh_access_token(
+    pub async fn mfa_verify(
         &self,
-        refresh_token: impl AsRef<str>,
+        jwt: impl AsRef<str>,
+        factor_id: impl AsRef<str>,
+        challenge_id: impl AsRef<str>,
+        code: impl AsRef<str>,
     ) -> Result<Session, reqwest::Error> {
-        let endpoint = format!("{}/token?grant_type=refresh_token", self.url);
-        let body = json!({ "refresh_token": refresh_token.as_ref() });
+        let endpoint = format!("{}/factors/{}/verify", self.url, factor_id.as_ref());
+
+        let body = json!({
+            "challenge_id": challenge_id.as_ref(),
+            "code": code.as_ref(),
+        });
 
         let session: Session = self
             .client
             .post(endpoint)
-            .headers(self.headers.clone())
+            .headers(self.authorized_headers(jwt.as_ref()))
             .json(&body)
             .send()
             .await?
@@ -338,10 +1375,10 @@ impl Api {
             .json()
             .await?;
 
-        return Ok(session);
+        Ok(session)
     }
 
-    /// Gets a user by access token
+    /// Removes a factor
     ///
     /// # Example
     ///
@@ -350,98 +1387,61 @@ impl Api {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Api::new("http://localhost:9998");
+    ///     let client = Api::new("http://localhost:9998");
     ///
     ///     let email = "email@example.com".to_string();
     ///     let password = "Abcd1234!";
     ///
     ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
-    ///     let user = client.get_user(&session.access_token);
+    ///     client.mfa_unenroll(&session.access_token, "factor_id").await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn get_user(&self, jwt: impl AsRef<str>) -> Result<User, reqwest::Error> {
-        let endpoint = format!("{}/user", self.url);
-
-        let mut headers: HeaderMap = self.headers.clone();
-        let bearer = format!("Bearer {}", jwt.as_ref());
-        headers.insert(
-            "Authorization",
-            HeaderValue::from_str(bearer.as_ref()).expect("Invalid header value."),
-        );
+    pub async fn mfa_unenroll(
+        &self,
+        jwt: impl AsRef<str>,
+        factor_id: impl AsRef<str>,
+    ) -> Result<bool, reqwest::Error> {
+        let endpoint = format!("{}/factors/{}", self.url, factor_id.as_ref());
 
-        let user: User = self
-            .client
-            .get(endpoint)
-            .headers(headers)
+        self.client
+            .delete(endpoint)
+            .headers(self.authorized_headers(jwt.as_ref()))
             .send()
             .await?
-            .error_for_status()?
-            .json()
-            .await?;
+            .error_for_status()?;
 
-        return Ok(user);
+        Ok(true)
     }
 
-    /// Updates a user
+    /// Lists the factors of the user
     ///
     /// # Example
     ///
     /// ```
-    /// use go_true::{Api, EmailOrPhone, UserAttributes};
-    /// use serde_json::json;
+    /// use go_true::{Api, EmailOrPhone};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Api::new("http://localhost:9998");
+    ///     let client = Api::new("http://localhost:9998");
     ///
     ///     let email = "email@example.com".to_string();
     ///     let password = "Abcd1234!";
     ///
-    ///     client.sign_up(EmailOrPhone::Email(email.clone()), password)
-    ///         .await?;
     ///     let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+    ///     let factors = client.mfa_list_factors(&session.access_token).await?;
     ///
-    ///     let new_email = "otheremail@example.com";
-    ///     let attributes = UserAttributes {
-    ///         email: new_email.to_string(),
-    ///         password: "Abcd12345!".to_string(),
-    ///         data: json!({ "test": "test" }),
-    ///     };
-    ///
-    ///     let updated_user = client.update_user(attributes, &session.access_token).await?;
     ///     Ok(())
     /// }
     /// ```
-    pub async fn update_user(
+    pub async fn mfa_list_factors(
         &self,
-        user: UserAttributes,
         jwt: impl AsRef<str>,
-    ) -> Result<UserUpdate, reqwest::Error> {
-        let endpoint = format!("{}/user", self.url);
-
-        let mut headers: HeaderMap = self.headers.clone();
-        let bearer = format!("Bearer {}", jwt.as_ref());
-        headers.insert(
-            "Authorization",
-            HeaderValue::from_str(bearer.as_ref()).expect("Invalid header value."),
-        );
+    ) -> Result<Vec<Factor>, reqwest::Error> {
+        let user = self.get_user(jwt).await?;
 
-        let body = json!({"email": user.email, "password": user.password, "data": user.data});
-
-        let user: UserUpdate = self
-            .client
-            .put(endpoint)
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<UserUpdate>()
-            .await?;
-
-        return Ok(user);
+        Ok(user.factors)
     }
 
     /// Invites a user via email
@@ -483,52 +1483,132 @@ impl Api {
             .json::<User>()
             .await?;
 
-        return Ok(user);
+        Ok(user)
     }
 
-    /// Lists all users based on a query string
+    /// Lists a page of users
     ///
     /// # Example
     ///
     /// ```
-    /// use go_true::{Api, EmailOrPhone};
+    /// use go_true::{Api, ListUsersOptions};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Api::new("http://localhost:9998");
-    ///
-    ///     let email = "email@example.com".to_string();
-    ///     let password = "Abcd1234!";
+    ///     let client = Api::new("http://localhost:9998");
     ///
-    ///     client
-    ///         .sign_up(EmailOrPhone::Email(email), password)
-    ///         .await?;
+    ///     let options = ListUsersOptions {
+    ///         page: Some(2),
+    ///         per_page: Some(100),
+    ///     };
     ///
-    ///     let users = client.list_users(None).await?;
+    ///     let users = client.list_users(Some(options)).await?;
+    ///     println!("{:?} users, next page {:?}", users.total, users.next_page);
     ///
     ///     Ok(())
     /// }
     /// ```
     pub async fn list_users(
         &self,
-        query_string: Option<String>,
+        options: Option<ListUsersOptions>,
     ) -> Result<UserList, reqwest::Error> {
-        let endpoint = match query_string {
-            Some(query) => format!("{}/admin/users{}", self.url, query),
-            None => format!("{}/admin/users", self.url),
-        };
+        let endpoint = format!("{}/admin/users", self.url);
+        let options = options.unwrap_or_default();
 
-        let users: UserList = self
-            .client
-            .get(endpoint)
-            .headers(self.headers.clone())
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
+        let mut request = self.client.get(endpoint).headers(self.headers.clone());
+        if let Some(page) = options.page {
+            request = request.query(&[("page", page)]);
+        }
+        if let Some(per_page) = options.per_page {
+            request = request.query(&[("per_page", per_page)]);
+        }
 
-        return Ok(users);
+        let response = request.send().await?.error_for_status()?;
+
+        let total = response
+            .headers()
+            .get("x-total-count")
+            .and_then(|total| total.to_str().ok())
+            .and_then(|total| total.parse().ok());
+
+        let (next_page, last_page) = response
+            .headers()
+            .get("link")
+            .and_then(|link| link.to_str().ok())
+            .map(parse_link_header)
+            .unwrap_or_default();
+
+        let mut users: UserList = response.json().await?;
+        users.total = total;
+        users.next_page = next_page;
+        users.last_page = last_page;
+
+        Ok(users)
+    }
+
+    /// Lists all users, fetching one page after another while the stream is consumed.
+    ///
+    /// The next page comes from the `Link` header. If a proxy strips it, the stream keeps
+    /// going until `X-Total-Count` users were yielded, or without that header until a page
+    /// comes back empty.
+    ///
+    /// GoTrue pages by offset, so users that are created or deleted while the stream is
+    /// consumed shift the pages. A user can then be yielded twice or be skipped; deduplicate
+    /// by id if that matters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// use go_true::Api;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Api::new("http://localhost:9998");
+    ///
+    ///     let users = client.list_all_users(Some(500));
+    ///     futures::pin_mut!(users);
+    ///
+    ///     while let Some(user) = users.try_next().await? {
+    ///         println!("{}", user.email);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn list_all_users(
+        &self,
+        per_page: Option<u32>,
+    ) -> impl Stream<Item = Result<User, reqwest::Error>> + '_ {
+        stream::try_unfold(Some((1, 0)), move |state| async move {
+            let (page, seen) = match state {
+                Some(state) => state,
+                None => return Ok(None),
+            };
+
+            let options = ListUsersOptions {
+                page: Some(page),
+                per_page,
+            };
+            let users = self.list_users(Some(options)).await?;
+            let seen = seen + users.users.len() as u64;
+
+            let has_more = if users.users.is_empty() {
+                false
+            } else if let Some(next_page) = users.next_page {
+                next_page > page
+            } else if let Some(total) = users.total {
+                seen < total
+            } else {
+                users.last_page.is_none()
+            };
+
+            Ok(Some((
+                stream::iter(users.users.into_iter().map(Ok)),
+                has_more.then_some((page + 1, seen)),
+            )))
+        })
+        .try_flatten()
     }
 
     /// Gets a user by id
@@ -545,11 +1625,11 @@ impl Api {
     ///     let email = "email@example.com".to_string();
     ///     let password = "Abcd1234!";
     ///
-    ///     let session = client
-    ///         .sign_up(EmailOrPhone::Email(email), password)
+    ///     let outcome = client
+    ///         .sign_up(EmailOrPhone::Email(email), password, None)
     ///         .await?;
     ///
-    ///     let user = client.get_user_by_id(&session.user.id).await?;
+    ///     let user = client.get_user_by_id(&outcome.user().id).await?;
     ///
     ///     Ok(())
     /// }
@@ -567,7 +1647,7 @@ impl Api {
             .json()
             .await?;
 
-        return Ok(user);
+        Ok(user)
     }
 
     /// Creates a user
@@ -575,42 +1655,37 @@ impl Api {
     /// # Example
     ///
     /// ```
-    /// use go_true::{Api};
+    /// use go_true::{AdminUserAttributes, Api};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let mut client = Api::new("http://localhost:9998");
     ///
-    ///     let user = AdminUserAttributes {
-    ///         email: "createemail@example.com".to_string(),
-    ///         password: Some(String::from("Abcd1234!")),
-    ///         data: None,
-    ///         email_confirmed: None,
-    ///         phone_confirmed: None,
-    ///     };
-
+    ///     let user = AdminUserAttributes::new()
+    ///         .email("createemail@example.com")
+    ///         .password("Abcd1234!")
+    ///         .email_confirm(true);
+    ///
     ///     client.create_user(user).await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn create_user<T: serde::Serialize>(&self, user: T) -> Result<User, reqwest::Error> {
+    pub async fn create_user(&self, user: AdminUserAttributes) -> Result<User, reqwest::Error> {
         let endpoint = format!("{}/admin/users", self.url);
 
-        let json = serde_json::to_value(&user).unwrap();
-
-        let client = reqwest::Client::new();
-        let user: User = client
+        let user: User = self
+            .client
             .post(endpoint)
             .headers(self.headers.clone())
-            .json(&json)
+            .json(&user)
             .send()
             .await?
             .error_for_status()?
             .json()
             .await?;
 
-        return Ok(user);
+        Ok(user)
     }
 
     /// Updates a user by id
@@ -618,57 +1693,45 @@ impl Api {
     /// # Example
     ///
     /// ```
-    /// use go_true::{Api};
+    /// use go_true::{AdminUserAttributes, Api};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let mut client = Api::new("http://localhost:9998");
     ///
-    ///     let user = AdminUserAttributes {
-    ///         email: "oldemail@example.com",
-    ///         password: Some(String::from("Abcd1234!")),
-    ///         data: None,
-    ///         email_confirmed: None,
-    ///         phone_confirmed: None,
-    ///     };
+    ///     let user = AdminUserAttributes::new()
+    ///         .email("oldemail@example.com")
+    ///         .password("Abcd1234!");
     ///
     ///     let create_response = client.create_user(user).await?;
-    ///     let user = AdminUserAttributes {
-    ///         email: "newemail@example.com".to_string(),
-    ///         password: None,
-    ///         data: None,
-    ///         email_confirmed: None,
-    ///         phone_confirmed: None,
-    ///     };
+    ///     let user = AdminUserAttributes::new().email("newemail@example.com");
     ///
     ///     let update_response = client
-    ///         .update_user_by_id(&create_response.id, user.clone())
+    ///         .update_user_by_id(&create_response.id, user)
     ///         .await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn update_user_by_id<T: serde::Serialize>(
+    pub async fn update_user_by_id(
         &self,
         id: impl AsRef<str>,
-        user: T,
+        user: AdminUserAttributes,
     ) -> Result<User, reqwest::Error> {
         let endpoint = format!("{}/admin/users/{}", self.url, id.as_ref());
 
-        let json = serde_json::to_value(&user).unwrap();
-
-        let client = reqwest::Client::new();
-        let user: User = client
+        let user: User = self
+            .client
             .put(endpoint)
             .headers(self.headers.clone())
-            .json(&json)
+            .json(&user)
             .send()
             .await?
             .error_for_status()?
             .json()
             .await?;
 
-        return Ok(user);
+        Ok(user)
     }
 
     /// Deletes a user by id
@@ -676,20 +1739,16 @@ impl Api {
     /// # Example
     ///
     /// ```
-    /// use go_true::{Api};
+    /// use go_true::{AdminUserAttributes, Api};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let mut client = Api::new("http://localhost:9998");
     ///
-    ///     let user = AdminUserAttributes {
-    ///         email: "delete@example.com".to_string(),
-    ///         password: Some(String::from("Abcd1234!")),
-    ///         data: None,
-    ///         email_confirmed: None,
-    ///         phone_confirmed: None,
-    ///     };
-
+    ///     let user = AdminUserAttributes::new()
+    ///         .email("delete@example.com")
+    ///         .password("Abcd1234!");
+    ///
     ///     let user = client.create_user(user).await?;
     ///     client.delete_user(&user.id).await?;
     ///
@@ -706,6 +1765,16 @@ impl Api {
             .await?
             .error_for_status()?;
 
-        return Ok(true);
+        Ok(true)
+    }
+
+    fn authorized_headers(&self, jwt: &str) -> HeaderMap {
+        let mut headers: HeaderMap = self.headers.clone();
+        let bearer = format!("Bearer {}", jwt);
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(bearer.as_ref()).expect("Invalid header value."),
+        );
+        headers
     }
 }
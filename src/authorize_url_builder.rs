@@ -0,0 +1,96 @@
+use url::form_urlencoded;
+
+use crate::{
+    oauth_options::OAuthOptions,
+    pkce::{Pkce, CODE_CHALLENGE_METHOD},
+    provider::Provider,
+};
+
+/// Builds the url that starts an OAuth sign in with a provider.
+///
+/// # Example
+///
+/// ```
+/// use go_true::{Api, Pkce, Provider};
+///
+/// let client = Api::new("http://localhost:9998");
+/// let pkce = Pkce::generate();
+///
+/// let url = client
+///     .authorize_url(Provider::Google)
+///     .redirect_to("https://your.app/callback")
+///     .scopes("email profile")
+///     .query_param("access_type", "offline")
+///     .query_param("prompt", "consent")
+///     .pkce(&pkce)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthorizeUrlBuilder {
+    url: String,
+    provider: Provider,
+    options: OAuthOptions,
+    code_challenge: Option<String>,
+}
+
+impl AuthorizeUrlBuilder {
+    pub(crate) fn new(url: &str, provider: Provider) -> AuthorizeUrlBuilder {
+        AuthorizeUrlBuilder {
+            url: url.to_string(),
+            provider,
+            options: OAuthOptions::default(),
+            code_challenge: None,
+        }
+    }
+
+    /// Sets where the user is redirected to after signing in.
+    pub fn redirect_to(mut self, redirect_to: impl Into<String>) -> Self {
+        self.options.redirect_to = Some(redirect_to.into());
+        self
+    }
+
+    /// Sets the space separated scopes requested from the provider.
+    pub fn scopes(mut self, scopes: impl Into<String>) -> Self {
+        self.options.scopes = Some(scopes.into());
+        self
+    }
+
+    /// Adds a query parameter that is passed on to the provider.
+    pub fn query_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.query_params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Replaces all options at once.
+    pub fn options(mut self, options: OAuthOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Uses the PKCE flow with the challenge of the given code verifier.
+    pub fn pkce(mut self, pkce: &Pkce) -> Self {
+        self.code_challenge = Some(pkce.code_challenge.clone());
+        self
+    }
+
+    pub fn build(self) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query.append_pair("provider", self.provider.as_str());
+
+        if let Some(redirect_to) = &self.options.redirect_to {
+            query.append_pair("redirect_to", redirect_to);
+        }
+        if let Some(scopes) = &self.options.scopes {
+            query.append_pair("scopes", scopes);
+        }
+        if let Some(code_challenge) = &self.code_challenge {
+            query.append_pair("code_challenge", code_challenge);
+            query.append_pair("code_challenge_method", CODE_CHALLENGE_METHOD);
+        }
+        for (key, value) in &self.options.query_params {
+            query.append_pair(key, value);
+        }
+
+        format!("{}/authorize?{}", self.url, query.finish())
+    }
+}
@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+/// The messaging channel used to deliver an OTP to a phone number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Sms,
+    Whatsapp,
+}
@@ -1,13 +1,43 @@
+use std::collections::HashMap;
+
+use url::{form_urlencoded, Url};
+
 use crate::{
-    api::{Api, EmailOrPhone},
+    anonymous_sign_in_options::AnonymousSignInOptions,
+    api::{Api, DomainOrProviderId, EmailOrPhone},
+    channel::Channel,
+    email_change_options::EmailChangeOptions,
+    email_change_outcome::EmailChangeOutcome,
+    email_change_request::EmailChangeRequest,
     error::Error,
+    health::{parse_version, Health},
+    id_token_credentials::IdTokenCredentials,
+    identity::Identity,
+    loopback::LoopbackListener,
+    mfa::{AuthenticatorAssuranceLevel, Challenge, EnrollParams, EnrollResponse, Factor},
+    oauth_options::OAuthOptions,
+    pkce::Pkce,
+    provider::Provider,
+    resend_options::{ResendOptions, ResendType},
+    reset_password_options::ResetPasswordOptions,
     session::Session,
+    settings::Settings,
+    sign_out_scope::SignOutScope,
+    sign_up_options::SignUpOptions,
+    sign_up_outcome::SignUpOutcome,
     user_attributes::UserAttributes,
     user_update::UserUpdate,
+    verify_otp_params::{EmailOtpType, VerifyOtpParams},
 };
 
+/// The first GoTrue release with the MFA API.
+const MFA_MIN_VERSION: &str = "v2.40.0";
+
 pub struct Client {
     current_session: Option<Session>,
+    password_recovery: bool,
+    settings: Option<Settings>,
+    server_version: Option<String>,
     api: Api,
 }
 
@@ -24,24 +54,34 @@ impl Client {
     pub fn new(url: impl Into<String>) -> Client {
         Client {
             current_session: None,
+            password_recovery: false,
+            settings: None,
+            server_version: None,
             api: Api::new(url),
         }
     }
 
-    /// Signs up a new user.
+    /// Signs up a new user. The current session is only set if GoTrue does not require the user
+    /// to confirm their email address or phone number first.
     ///
     /// # Example
     ///
     /// ```
-    /// use go_true::{Client, EmailOrPhone};
+    /// use go_true::{Client, EmailOrPhone, SignUpOptions};
+    /// use serde_json::json;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let mut client = Client::new("http://your.gotrue.endpoint");
     ///     let email = "some_email".to_string();
     ///     let password = "some_password";
+    ///     let options = SignUpOptions {
+    ///         data: Some(json!({ "name": "Some Name" })),
+    ///         email_redirect_to: Some("https://your.app/welcome".to_string()),
+    ///         ..Default::default()
+    ///     };
     ///     let res = client
-    ///         .sign_up(EmailOrPhone::Email(email), password)
+    ///         .sign_up(EmailOrPhone::Email(email), password, Some(options))
     ///         .await?;
     ///     Ok(())
     /// }
@@ -49,20 +89,22 @@ impl Client {
         &mut self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str>,
-    ) -> Result<Session, Error> {
+        options: Option<SignUpOptions>,
+    ) -> Result<SignUpOutcome, Error> {
         self.current_session = None;
-        let result = self.api.sign_up(email_or_phone, password).await;
+        self.password_recovery = false;
+        let result = self.api.sign_up(email_or_phone, password, options).await;
 
         match result {
-            Ok(session) => {
-                self.current_session = Some(session.clone());
-                return Ok(session);
+            Ok(outcome) => {
+                self.current_session = outcome.session().cloned();
+                Ok(outcome)
             }
             Err(e) => {
-                if e.is_status() && e.status().unwrap().as_str() == "400" {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "422") {
                     return Err(Error::AlreadySignedUp);
                 }
-                return Err(Error::InternalError);
+                Err(Error::InternalError)
             }
         }
     }
@@ -90,19 +132,94 @@ impl Client {
         password: impl AsRef<str>,
     ) -> Result<Session, Error> {
         self.current_session = None;
+        self.password_recovery = false;
         let result = self.api.sign_in(email_or_phone, password).await;
 
         match result {
             Ok(session) => {
                 self.current_session = Some(session.clone());
-                return Ok(session);
+                Ok(session)
             }
             Err(e) => {
                 if e.is_status() && e.status().unwrap().as_str() == "400" {
                     return Err(Error::WrongCredentials);
                 }
-                return Err(Error::InternalError);
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Signs in with an ID token issued by an OIDC provider
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client, IdTokenCredentials, Provider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///     let credentials = IdTokenCredentials {
+    ///         provider: Provider::Apple,
+    ///         id_token: "id_token_from_apple".to_string(),
+    ///         access_token: None,
+    ///         nonce: Some("raw_nonce".to_string()),
+    ///     };
+    ///
+    ///     let session = client.sign_in_with_id_token(credentials).await?;
+    ///     Ok(())
+    /// }
+    pub async fn sign_in_with_id_token(
+        &mut self,
+        credentials: IdTokenCredentials,
+    ) -> Result<Session, Error> {
+        self.current_session = None;
+        self.password_recovery = false;
+        let result = self.api.sign_in_with_id_token(credentials).await;
+
+        match result {
+            Ok(session) => {
+                self.current_session = Some(session.clone());
+                Ok(session)
             }
+            Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "400" {
+                    return Err(Error::WrongToken);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Signs in as a new anonymous user. The user can be converted into a permanent user later
+    /// by setting an email address or phone number with `update_user` and confirming it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     let session = client.sign_in_anonymously(None).await?;
+    ///     Ok(())
+    /// }
+    pub async fn sign_in_anonymously(
+        &mut self,
+        options: Option<AnonymousSignInOptions>,
+    ) -> Result<Session, Error> {
+        self.current_session = None;
+        self.password_recovery = false;
+        let result = self.api.sign_in_anonymously(options).await;
+
+        match result {
+            Ok(session) => {
+                self.current_session = Some(session.clone());
+                Ok(session)
+            }
+            Err(_) => Err(Error::InternalError),
         }
     }
 
@@ -131,60 +248,163 @@ impl Client {
         let result = self.api.send_otp(email_or_phone, should_create_user).await;
 
         match result {
-            Ok(_) => return Ok(true),
+            Ok(_) => Ok(true),
             Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "400" {
+                    return Err(Error::InvalidInput);
+                }
                 if e.is_status() && e.status().unwrap().as_str() == "422" {
                     return Err(Error::UserNotFound);
                 }
-                return Err(Error::InternalError);
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Resends a signup confirmation, email change or phone OTP
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client, EmailOrPhone, ResendOptions, ResendType};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///     let email = "some_email".to_string();
+    ///     let options = ResendOptions {
+    ///         email_redirect_to: Some("https://your.app/welcome".to_string()),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let res = client
+    ///         .resend(ResendType::Signup, EmailOrPhone::Email(email), Some(options))
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    pub async fn resend(
+        &self,
+        resend_type: ResendType,
+        email_or_phone: EmailOrPhone,
+        options: Option<ResendOptions>,
+    ) -> Result<bool, Error> {
+        let result = self.api.resend(resend_type, email_or_phone, options).await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "400" {
+                    return Err(Error::InvalidInput);
+                }
+                if e.is_status() && e.status().unwrap().as_str() == "422" {
+                    return Err(Error::UserNotFound);
+                }
+                Err(Error::InternalError)
             }
         }
     }
 
-    pub async fn verify_otp<T: serde::Serialize>(&mut self, params: T) -> Result<bool, Error> {
+    /// Verifies an OTP and stores the returned session
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client, EmailOtpType, VerifyOtpParams};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///     let params = VerifyOtpParams::Email {
+    ///         email: "some_email".to_string(),
+    ///         token: "123456".to_string(),
+    ///         otp_type: EmailOtpType::Magiclink,
+    ///     };
+    ///
+    ///     let session = client.verify_otp(params).await?;
+    ///     Ok(())
+    /// }
+    pub async fn verify_otp(&mut self, params: VerifyOtpParams) -> Result<Session, Error> {
         self.current_session = None;
+        self.password_recovery = false;
+
+        let is_recovery = matches!(
+            params,
+            VerifyOtpParams::Email {
+                otp_type: EmailOtpType::Recovery,
+                ..
+            } | VerifyOtpParams::TokenHash {
+                otp_type: EmailOtpType::Recovery,
+                ..
+            }
+        );
         let result = self.api.verify_otp(params).await;
 
         match result {
-            Ok(_) => return Ok(true),
+            Ok(session) => {
+                self.current_session = Some(session.clone());
+                self.password_recovery = is_recovery;
+                Ok(session)
+            }
             Err(e) => {
-                if e.is_status() && e.status().unwrap().as_str() == "400" {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "401" | "403") {
                     return Err(Error::WrongToken);
                 }
-                return Err(Error::InternalError);
+                Err(Error::InternalError)
             }
         }
     }
 
-    /// Sign out the current user
+    /// Sign out the current user. The current session is dropped unless the scope is
+    /// `SignOutScope::Others`, which only revokes the user's other sessions. It is also dropped
+    /// if its token is already expired or revoked.
     ///
     /// # Example
     ///
     /// ```
-    /// use go_true::{Client};
+    /// use go_true::{Client, SignOutScope};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
     ///
     ///     // Sign in first
     ///
-    ///     let res = client.sign_out().await?;
+    ///     let res = client.sign_out(Some(SignOutScope::Local)).await?;
     ///     Ok(())
     /// }
-    pub async fn sign_out(&self) -> Result<bool, Error> {
+    pub async fn sign_out(&mut self, scope: Option<SignOutScope>) -> Result<bool, Error> {
         let result = match &self.current_session {
-            Some(session) => self.api.sign_out(&session.access_token).await,
+            Some(session) => self.api.sign_out(&session.access_token, scope).await,
             None => return Err(Error::NotAuthenticated),
         };
 
-        match result {
-            Ok(_) => return Ok(true),
-            Err(_) => return Err(Error::InternalError),
+        // An expired or revoked token means the session is already gone on the server
+        let signed_out = match &result {
+            Ok(_) => true,
+            Err(e) => {
+                e.is_status() && matches!(e.status().unwrap().as_str(), "401" | "403" | "404")
+            }
+        };
+
+        if !signed_out {
+            return Err(Error::InternalError);
+        }
+
+        if scope != Some(SignOutScope::Others) {
+            self.current_session = None;
+            self.password_recovery = false;
         }
+
+        Ok(true)
     }
 
-    /// Reset a user's password for an email address
+    /// Reset a user's password for an email address. Once the user comes back from the
+    /// recovery link through `exchange_code_for_session`, `verify_otp` or
+    /// `get_session_from_url`, the client is in password recovery and the new password is set
+    /// with `complete_password_recovery`.
+    ///
+    /// GoTrue answers unknown addresses like known ones so it doesn't reveal which users
+    /// exist. A malformed address fails with `Error::InvalidInput`.
     ///
     /// # Example
     ///
@@ -196,16 +416,78 @@ impl Client {
     ///     let client = Client::new("http://your.gotrue.endpoint");
     ///     let email = "some_email";
     ///
-    ///     let res = client.reset_password_for_email(email).await?;
+    ///     let res = client.reset_password_for_email(email, None).await?;
     ///     Ok(())
     /// }
-    pub async fn reset_password_for_email(&self, email: impl AsRef<str>) -> Result<bool, Error> {
-        let result = self.api.reset_password_for_email(email).await;
+    pub async fn reset_password_for_email(
+        &self,
+        email: impl AsRef<str>,
+        options: Option<ResetPasswordOptions>,
+    ) -> Result<bool, Error> {
+        let result = self.api.reset_password_for_email(email, options).await;
 
         match result {
-            Ok(_) => return Ok(true),
-            Err(_) => return Err(Error::UserNotFound),
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "422") {
+                    return Err(Error::InvalidInput);
+                }
+                if e.is_status() && e.status().unwrap().as_str() == "429" {
+                    return Err(Error::RateLimited);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Whether the current session was issued for a password recovery link
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    /// assert!(!client.is_password_recovery());
+    /// ```
+    pub fn is_password_recovery(&self) -> bool {
+        self.password_recovery
+    }
+
+    /// Sets the new password of a user in password recovery
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // exchange the code from the recovery link first
+    ///
+    ///     let res = client.complete_password_recovery("Abcd12345!").await?;
+    ///     Ok(())
+    /// }
+    pub async fn complete_password_recovery(
+        &mut self,
+        password: impl Into<String>,
+    ) -> Result<UserUpdate, Error> {
+        if !self.password_recovery {
+            return Err(Error::NoPasswordRecovery);
         }
+
+        let attributes = UserAttributes {
+            password: Some(password.into()),
+            ..Default::default()
+        };
+
+        let update = self.update_user(attributes).await?;
+        self.password_recovery = false;
+
+        Ok(update)
     }
 
     pub async fn update_user(&self, user: UserAttributes) -> Result<UserUpdate, Error> {
@@ -217,17 +499,22 @@ impl Client {
         let result = self.api.update_user(user, &session.access_token).await;
 
         match result {
-            Ok(user) => return Ok(user),
+            Ok(user) => Ok(user),
             Err(e) => {
-                if e.is_status() && e.status().unwrap().as_str() == "400" {
-                    return Err(Error::UserNotFound);
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "422") {
+                    return Err(Error::InvalidInput);
                 }
-                return Err(Error::InternalError);
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "401" | "403") {
+                    return Err(Error::NotAuthenticated);
+                }
+                Err(Error::InternalError)
             }
         }
     }
 
-    /// Refreshes the current session
+    /// Requests to change the email address of the current user. The change is finished with
+    /// `verify_email_change` for every pending confirmation. Fails with
+    /// `Error::EmailNotAvailable` if the address is invalid or taken.
     ///
     /// # Example
     ///
@@ -236,34 +523,40 @@ impl Client {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///     let client = Client::new("http://your.gotrue.endpoint");
     ///
     ///     // sign in first
     ///
-    ///     client.refresh_session().await?;
+    ///     let request = client.request_email_change("new@example.com", None).await?;
     ///     Ok(())
     /// }
-    pub async fn refresh_session(&mut self) -> Result<Session, Error> {
-        if self.current_session.is_none() {
-            return Err(Error::NotAuthenticated);
-        }
-
+    pub async fn request_email_change(
+        &self,
+        new_email: impl AsRef<str>,
+        options: Option<EmailChangeOptions>,
+    ) -> Result<EmailChangeRequest, Error> {
         let result = match &self.current_session {
-            Some(session) => self.api.refresh_access_token(&session.refresh_token).await,
-            None => return Err(Error::MissingRefreshToken),
-        };
-
-        let session = match result {
-            Ok(session) => session,
-            Err(_) => return Err(Error::InternalError),
+            Some(session) => {
+                self.api
+                    .request_email_change(&session.access_token, new_email, options)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
         };
 
-        self.current_session = Some(session.clone());
-
-        return Ok(session);
+        match result {
+            Ok(request) => Ok(request),
+            Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "422" {
+                    return Err(Error::EmailNotAvailable);
+                }
+                Err(Error::InternalError)
+            }
+        }
     }
 
-    /// Sets a session by refresh token
+    /// Verifies a token of an email change. The session is replaced once the change is
+    /// completed.
     ///
     /// # Example
     ///
@@ -273,25 +566,866 @@ impl Client {
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let mut client = Client::new("http://your.gotrue.endpoint");
-    ///     let token = "refresh_token";
     ///
-    ///     let session = client.set_session(token).await?;
+    ///     let outcome = client
+    ///         .verify_email_change("new@example.com", "123456")
+    ///         .await?;
     ///     Ok(())
     /// }
-    pub async fn set_session(&mut self, refresh_token: impl AsRef<str>) -> Result<Session, Error> {
-        if refresh_token.as_ref().len() < 1 {
-            return Err(Error::NotAuthenticated);
-        }
+    pub async fn verify_email_change(
+        &mut self,
+        email: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Result<EmailChangeOutcome, Error> {
+        let result = self.api.verify_email_change(email, token).await;
 
-        let result = self.api.refresh_access_token(refresh_token).await;
+        match result {
+            Ok(outcome) => {
+                if let Some(session) = outcome.session() {
+                    self.current_session = Some(session.clone());
+                }
+                Ok(outcome)
+            }
+            Err(e) => {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "401" | "403") {
+                    return Err(Error::WrongToken);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
 
-        let session = match result {
-            Ok(session) => session,
-            Err(_) => return Err(Error::InternalError),
+    /// Requests to change the phone number of the current user. The change is finished with
+    /// `verify_phone_change`. Fails with `Error::PhoneNotAvailable` if the number is invalid or
+    /// taken.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let update = client.request_phone_change("+4912345678", None).await?;
+    ///     Ok(())
+    /// }
+    pub async fn request_phone_change(
+        &self,
+        new_phone: impl AsRef<str>,
+        channel: Option<Channel>,
+    ) -> Result<UserUpdate, Error> {
+        let result = match &self.current_session {
+            Some(session) => {
+                self.api
+                    .request_phone_change(&session.access_token, new_phone, channel)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
         };
 
-        self.current_session = Some(session.clone());
+        match result {
+            Ok(update) => Ok(update),
+            Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "422" {
+                    return Err(Error::PhoneNotAvailable);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Verifies the OTP sent to the new phone number and replaces the current session
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     let session = client.verify_phone_change("+4912345678", "123456").await?;
+    ///     Ok(())
+    /// }
+    pub async fn verify_phone_change(
+        &mut self,
+        phone: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Result<Session, Error> {
+        let result = self.api.verify_phone_change(phone, token).await;
 
-        return Ok(session);
+        match result {
+            Ok(session) => {
+                self.current_session = Some(session.clone());
+                Ok(session)
+            }
+            Err(e) => {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "401" | "403") {
+                    return Err(Error::WrongToken);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Exchanges an auth code from the PKCE flow for a session
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///     let auth_code = "auth_code";
+    ///     let code_verifier = "code_verifier";
+    ///
+    ///     let session = client
+    ///         .exchange_code_for_session(auth_code, code_verifier)
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    pub async fn exchange_code_for_session(
+        &mut self,
+        auth_code: impl AsRef<str>,
+        code_verifier: impl AsRef<str>,
+    ) -> Result<Session, Error> {
+        self.current_session = None;
+        self.password_recovery = false;
+        let result = self
+            .api
+            .exchange_code_for_session(auth_code, code_verifier)
+            .await;
+
+        match result {
+            Ok(session) => {
+                self.password_recovery = AuthenticatorAssuranceLevel::from_session(&session)
+                    .map(|level| {
+                        level
+                            .current_authentication_methods
+                            .iter()
+                            .any(|method| method.method == "recovery")
+                    })
+                    .unwrap_or(false);
+                self.current_session = Some(session.clone());
+                Ok(session)
+            }
+            Err(e) => {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "404") {
+                    return Err(Error::WrongToken);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Signs in with an OAuth provider from a CLI or desktop app. Starts a listener on a free
+    /// port of 127.0.0.1, passes the authorize url to `open_url`, which should open it in the
+    /// browser, and exchanges the code from the redirect for a session. Fails with
+    /// `Error::RedirectTimeout` if the user doesn't finish signing in within 5 minutes.
+    ///
+    /// The loopback url has to be on the server's redirect allow-list, for example with
+    /// `GOTRUE_URI_ALLOW_LIST=http://127.0.0.1:*/callback`. Otherwise GoTrue redirects to its
+    /// site url instead and the listener only returns once it times out.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use go_true::{Client, Provider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     let result = client
+    ///         .sign_in_with_loopback(Provider::Github, None, |url| println!("Open {}", url))
+    ///         .await;
+    /// }
+    pub async fn sign_in_with_loopback(
+        &mut self,
+        provider: Provider,
+        options: Option<OAuthOptions>,
+        open_url: impl FnOnce(&str),
+    ) -> Result<Session, Error> {
+        let listener = match LoopbackListener::bind(0).await {
+            Ok(listener) => listener,
+            Err(_) => return Err(Error::InternalError),
+        };
+        let pkce = Pkce::generate();
+
+        let url = self
+            .api
+            .authorize_url(provider)
+            .options(options.unwrap_or_default())
+            .redirect_to(listener.redirect_url())
+            .pkce(&pkce)
+            .build();
+
+        open_url(&url);
+
+        let code = listener.wait_for_code().await?;
+        self.exchange_code_for_session(code, &pkce.code_verifier)
+            .await
+    }
+
+    /// Sends a reauthentication nonce to the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     client.reauthenticate().await?;
+    ///     Ok(())
+    /// }
+    pub async fn reauthenticate(&self) -> Result<bool, Error> {
+        let result = match &self.current_session {
+            Some(session) => self.api.reauthenticate(&session.access_token).await,
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Gets the url of the SAML identity provider for enterprise SSO
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client, DomainOrProviderId};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///     let domain = DomainOrProviderId::Domain("company.com".to_string());
+    ///
+    ///     let url = client.sign_in_with_sso(domain, None).await?;
+    ///     Ok(())
+    /// }
+    pub async fn sign_in_with_sso(
+        &self,
+        domain_or_provider_id: DomainOrProviderId,
+        redirect_to: Option<String>,
+    ) -> Result<String, Error> {
+        let result = self
+            .api
+            .sign_in_with_sso(domain_or_provider_id, redirect_to)
+            .await;
+
+        match result {
+            Ok(url) => Ok(url),
+            Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "404" {
+                    return Err(Error::SsoProviderNotFound);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Gets the url for linking an OAuth identity to the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client, OAuthOptions, Provider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let options = OAuthOptions {
+    ///         redirect_to: Some("https://your.app/settings".to_string()),
+    ///         ..Default::default()
+    ///     };
+    ///     let url = client.link_identity(Provider::Github, Some(options)).await?;
+    ///     Ok(())
+    /// }
+    pub async fn link_identity(
+        &self,
+        provider: Provider,
+        options: Option<OAuthOptions>,
+    ) -> Result<String, Error> {
+        let result = match &self.current_session {
+            Some(session) => {
+                self.api
+                    .link_identity(&session.access_token, provider, options)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(url) => Ok(url),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Checks the health of the GoTrue server and remembers its version. Once the version is
+    /// known, calls the server doesn't support fail with `Error::UnsupportedServerVersion`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     let health = client.health().await?;
+    ///     Ok(())
+    /// }
+    pub async fn health(&mut self) -> Result<Health, Error> {
+        let health = match self.api.health().await {
+            Ok(health) => health,
+            Err(_) => return Err(Error::InternalError),
+        };
+
+        self.server_version = Some(health.version.clone());
+
+        Ok(health)
+    }
+
+    /// The version of the GoTrue server, known after calling `health`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    /// assert_eq!(client.server_version(), None);
+    /// ```
+    pub fn server_version(&self) -> Option<&str> {
+        self.server_version.as_deref()
+    }
+
+    /// Sets the version of the GoTrue server when it is already known, instead of asking the
+    /// server with `health`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// let mut client = Client::new("http://your.gotrue.endpoint");
+    /// client.set_server_version("v2.164.0");
+    ///
+    /// assert_eq!(client.server_version(), Some("v2.164.0"));
+    /// ```
+    pub fn set_server_version(&mut self, version: impl Into<String>) {
+        self.server_version = Some(version.into());
+    }
+
+    /// Gets the settings of the GoTrue server. They are fetched once and cached afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     let settings = client.get_settings().await?;
+    ///     Ok(())
+    /// }
+    pub async fn get_settings(&mut self) -> Result<Settings, Error> {
+        if let Some(settings) = &self.settings {
+            return Ok(settings.clone());
+        }
+
+        let settings = match self.api.get_settings().await {
+            Ok(settings) => settings,
+            Err(_) => return Err(Error::InternalError),
+        };
+
+        self.settings = Some(settings.clone());
+
+        Ok(settings)
+    }
+
+    /// Fetches the settings of the GoTrue server again and replaces the cached ones
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     let settings = client.refresh_settings().await?;
+    ///     Ok(())
+    /// }
+    pub async fn refresh_settings(&mut self) -> Result<Settings, Error> {
+        self.settings = None;
+        self.get_settings().await
+    }
+
+    /// Gets the identities of the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let identities = client.get_user_identities().await?;
+    ///     Ok(())
+    /// }
+    pub async fn get_user_identities(&self) -> Result<Vec<Identity>, Error> {
+        let result = match &self.current_session {
+            Some(session) => self.api.get_user(&session.access_token).await,
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(user) => Ok(user.identities.unwrap_or_default()),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Unlinks an identity from the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let identities = client.get_user_identities().await?;
+    ///     client.unlink_identity(&identities[0].identity_id).await?;
+    ///     Ok(())
+    /// }
+    pub async fn unlink_identity(&self, identity_id: impl AsRef<str>) -> Result<bool, Error> {
+        let result = match &self.current_session {
+            Some(session) => {
+                self.api
+                    .unlink_identity(&session.access_token, identity_id)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Enrolls a new factor for the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{mfa::EnrollParams, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let params = EnrollParams::Totp {
+    ///         friendly_name: None,
+    ///         issuer: None,
+    ///     };
+    ///     let factor = client.mfa_enroll(params).await?;
+    ///     Ok(())
+    /// }
+    pub async fn mfa_enroll(&self, params: EnrollParams) -> Result<EnrollResponse, Error> {
+        self.require_version(MFA_MIN_VERSION)?;
+
+        let result = match &self.current_session {
+            Some(session) => self.api.mfa_enroll(&session.access_token, params).await,
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(response) => Ok(response),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Creates a challenge for a factor of the current user. For phone factors the code is
+    /// sent via the given channel, which defaults to SMS.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let challenge = client.mfa_challenge("factor_id", None).await?;
+    ///     Ok(())
+    /// }
+    pub async fn mfa_challenge(
+        &self,
+        factor_id: impl AsRef<str>,
+        channel: Option<Channel>,
+    ) -> Result<Challenge, Error> {
+        self.require_version(MFA_MIN_VERSION)?;
+
+        let result = match &self.current_session {
+            Some(session) => {
+                self.api
+                    .mfa_challenge(&session.access_token, factor_id, channel)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(challenge) => Ok(challenge),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Verifies a challenge and replaces the current session with the upgraded one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let challenge = client.mfa_challenge("factor_id", None).await?;
+    ///     let session = client
+    ///         .mfa_verify("factor_id", &challenge.id, "123456")
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    pub async fn mfa_verify(
+        &mut self,
+        factor_id: impl AsRef<str>,
+        challenge_id: impl AsRef<str>,
+        code: impl AsRef<str>,
+    ) -> Result<Session, Error> {
+        self.require_version(MFA_MIN_VERSION)?;
+
+        let result = match &self.current_session {
+            Some(session) => {
+                self.api
+                    .mfa_verify(&session.access_token, factor_id, challenge_id, code)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(session) => {
+                self.current_session = Some(session.clone());
+                Ok(session)
+            }
+            Err(e) => {
+                if e.is_status() && matches!(e.status().unwrap().as_str(), "400" | "422") {
+                    return Err(Error::WrongToken);
+                }
+                Err(Error::InternalError)
+            }
+        }
+    }
+
+    /// Removes a factor of the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     client.mfa_unenroll("factor_id").await?;
+    ///     Ok(())
+    /// }
+    pub async fn mfa_unenroll(&self, factor_id: impl AsRef<str>) -> Result<bool, Error> {
+        self.require_version(MFA_MIN_VERSION)?;
+
+        let result = match &self.current_session {
+            Some(session) => {
+                self.api
+                    .mfa_unenroll(&session.access_token, factor_id)
+                    .await
+            }
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Lists the factors of the current user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let factors = client.mfa_list_factors().await?;
+    ///     Ok(())
+    /// }
+    pub async fn mfa_list_factors(&self) -> Result<Vec<Factor>, Error> {
+        self.require_version(MFA_MIN_VERSION)?;
+
+        let result = match &self.current_session {
+            Some(session) => self.api.mfa_list_factors(&session.access_token).await,
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match result {
+            Ok(factors) => Ok(factors),
+            Err(_) => Err(Error::InternalError),
+        }
+    }
+
+    /// Gets the assurance level of the current session and the level the user can reach by
+    /// verifying one of their factors
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{mfa::AssuranceLevel, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     let aal = client.get_authenticator_assurance_level()?;
+    ///     if aal.next_level == Some(AssuranceLevel::Aal2) && aal.current_level != aal.next_level {
+    ///         // challenge and verify a factor
+    ///     }
+    ///     Ok(())
+    /// }
+    pub fn get_authenticator_assurance_level(&self) -> Result<AuthenticatorAssuranceLevel, Error> {
+        let session = match &self.current_session {
+            Some(s) => s,
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match AuthenticatorAssuranceLevel::from_session(session) {
+            Some(aal) => Ok(aal),
+            None => Err(Error::WrongToken),
+        }
+    }
+
+    /// Parses the session from the fragment of an implicit flow callback url, fetches its user
+    /// and stores it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///     let url = "http://your.app/callback#access_token=token&refresh_token=token\
+    ///                &expires_in=3600&token_type=bearer";
+    ///
+    ///     let session = client.get_session_from_url(url).await?;
+    ///     Ok(())
+    /// }
+    pub async fn get_session_from_url(&mut self, url: impl AsRef<str>) -> Result<Session, Error> {
+        let url = match Url::parse(url.as_ref()) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::InvalidCallbackUrl),
+        };
+
+        let params: HashMap<String, String> = match url.fragment() {
+            Some(fragment) => form_urlencoded::parse(fragment.as_bytes())
+                .into_owned()
+                .collect(),
+            None => return Err(Error::InvalidCallbackUrl),
+        };
+
+        if let Some(error) = params.get("error") {
+            return Err(Error::CallbackError {
+                error: error.clone(),
+                error_code: params.get("error_code").cloned(),
+                description: params.get("error_description").cloned(),
+            });
+        }
+
+        let (access_token, refresh_token, token_type, expires_in) = match (
+            params.get("access_token"),
+            params.get("refresh_token"),
+            params.get("token_type"),
+            params.get("expires_in").and_then(|e| e.parse().ok()),
+        ) {
+            (Some(access_token), Some(refresh_token), Some(token_type), Some(expires_in)) => {
+                (access_token, refresh_token, token_type, expires_in)
+            }
+            _ => return Err(Error::InvalidCallbackUrl),
+        };
+
+        let user = match self.api.get_user(access_token).await {
+            Ok(user) => user,
+            Err(e) => {
+                if e.is_status() && e.status().unwrap().as_str() == "401" {
+                    return Err(Error::WrongToken);
+                }
+                return Err(Error::InternalError);
+            }
+        };
+
+        let session = Session {
+            access_token: access_token.clone(),
+            token_type: token_type.clone(),
+            expires_in,
+            refresh_token: refresh_token.clone(),
+            expires_at: params.get("expires_at").and_then(|e| e.parse().ok()),
+            provider_token: params.get("provider_token").cloned(),
+            provider_refresh_token: params.get("provider_refresh_token").cloned(),
+            user,
+        };
+
+        self.password_recovery = params.get("type").map(String::as_str) == Some("recovery");
+        self.current_session = Some(session.clone());
+
+        Ok(session)
+    }
+
+    /// Refreshes the current session
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///
+    ///     // sign in first
+    ///
+    ///     client.refresh_session().await?;
+    ///     Ok(())
+    /// }
+    pub async fn refresh_session(&mut self) -> Result<Session, Error> {
+        if self.current_session.is_none() {
+            return Err(Error::NotAuthenticated);
+        }
+
+        let result = match &self.current_session {
+            Some(session) => self.api.refresh_access_token(&session.refresh_token).await,
+            None => return Err(Error::MissingRefreshToken),
+        };
+
+        let session = match result {
+            Ok(session) => session,
+            Err(_) => return Err(Error::InternalError),
+        };
+
+        self.current_session = Some(session.clone());
+
+        Ok(session)
+    }
+
+    /// Sets a session by refresh token
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::{Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = Client::new("http://your.gotrue.endpoint");
+    ///     let token = "refresh_token";
+    ///
+    ///     let session = client.set_session(token).await?;
+    ///     Ok(())
+    /// }
+    pub async fn set_session(&mut self, refresh_token: impl AsRef<str>) -> Result<Session, Error> {
+        if refresh_token.as_ref().is_empty() {
+            return Err(Error::NotAuthenticated);
+        }
+
+        let result = self.api.refresh_access_token(refresh_token).await;
+
+        let session = match result {
+            Ok(session) => session,
+            Err(_) => return Err(Error::InternalError),
+        };
+
+        self.password_recovery = false;
+        self.current_session = Some(session.clone());
+
+        Ok(session)
+    }
+
+    /// Fails if the server version is known and older than `required`. Versions that can't be
+    /// parsed, like development builds, are assumed to support everything.
+    fn require_version(&self, required: &str) -> Result<(), Error> {
+        let version = match &self.server_version {
+            Some(version) => version,
+            None => return Ok(()),
+        };
+
+        match (parse_version(version), parse_version(required)) {
+            (Some(current), Some(minimum)) if current < minimum => {
+                Err(Error::UnsupportedServerVersion {
+                    version: version.clone(),
+                    required: required.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
     }
 }
@@ -0,0 +1,9 @@
+/// Optional parameters for requesting an email change.
+#[derive(Debug, Clone, Default)]
+pub struct EmailChangeOptions {
+    /// Where the user is redirected to from the confirmation links.
+    pub email_redirect_to: Option<String>,
+    /// Whether secure email change is enabled on the server, which it is by default. GoTrue
+    /// does not expose this setting, so without it the pending confirmations are unknown.
+    pub secure_email_change: Option<bool>,
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+use crate::session::Session;
+
+/// The result of verifying an email change. With secure email change enabled GoTrue sends a
+/// token to both the current and the new address, and only the second verification completes
+/// the change.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EmailChangeOutcome {
+    Completed(Box<Session>),
+    /// The address was confirmed, but the other one of a secure email change still has to be.
+    /// `msg` is GoTrue's explanation.
+    OtherAddressPending {
+        msg: String,
+    },
+}
+
+impl EmailChangeOutcome {
+    /// Returns the session if the email change is completed.
+    pub fn session(&self) -> Option<&Session> {
+        match self {
+            EmailChangeOutcome::Completed(session) => Some(session),
+            EmailChangeOutcome::OtherAddressPending { .. } => None,
+        }
+    }
+}
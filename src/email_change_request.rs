@@ -0,0 +1,41 @@
+use crate::user_update::UserUpdate;
+
+/// The addresses that still have to confirm a requested email change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingConfirmations {
+    /// GoTrue applied the change right away, as it does with mailer autoconfirm.
+    None,
+    /// Only the new address has to be confirmed, because the user had no email address yet or
+    /// secure email change is disabled.
+    NewAddress,
+    /// Both the current and the new address have to be confirmed.
+    BothAddresses,
+    /// The new address and possibly the current one have to be confirmed, depending on whether
+    /// secure email change is enabled.
+    Unknown,
+}
+
+/// A requested email change and the confirmations it is waiting for.
+#[derive(Debug)]
+pub struct EmailChangeRequest {
+    pub user: UserUpdate,
+    pub pending: PendingConfirmations,
+}
+
+impl EmailChangeRequest {
+    pub(crate) fn new(user: UserUpdate, secure_email_change: Option<bool>) -> EmailChangeRequest {
+        let pending = if user.new_email.is_none() {
+            PendingConfirmations::None
+        } else if user.email.is_empty() {
+            PendingConfirmations::NewAddress
+        } else {
+            match secure_email_change {
+                Some(true) => PendingConfirmations::BothAddresses,
+                Some(false) => PendingConfirmations::NewAddress,
+                None => PendingConfirmations::Unknown,
+            }
+        };
+
+        EmailChangeRequest { user, pending }
+    }
+}
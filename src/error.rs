@@ -1,11 +1,28 @@
 #[derive(Debug)]
 pub enum Error {
     AlreadySignedUp,
+    EmailNotAvailable,
+    PhoneNotAvailable,
     WrongCredentials,
     UserNotFound,
+    InvalidInput,
     NotAuthenticated,
     MissingRefreshToken,
     WrongToken,
+    SsoProviderNotFound,
+    InvalidCallbackUrl,
+    RedirectTimeout,
+    RateLimited,
+    NoPasswordRecovery,
+    UnsupportedServerVersion {
+        version: String,
+        required: String,
+    },
+    CallbackError {
+        error: String,
+        error_code: Option<String>,
+        description: Option<String>,
+    },
     InternalError,
 }
 
@@ -15,11 +32,34 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Error::AlreadySignedUp => write!(f, "User already signed up."),
+            Error::EmailNotAvailable => {
+                write!(f, "The email address is invalid or already in use.")
+            }
+            Error::PhoneNotAvailable => {
+                write!(f, "The phone number is invalid or already in use.")
+            }
             Error::WrongCredentials => write!(f, "Wrong credentials."),
             Error::UserNotFound => write!(f, "User not found."),
+            Error::InvalidInput => write!(f, "Invalid input."),
             Error::NotAuthenticated => write!(f, "User is not authenticated."),
             Error::MissingRefreshToken => write!(f, "Refresh Token is missing"),
             Error::WrongToken => write!(f, "Wrong token."),
+            Error::SsoProviderNotFound => write!(f, "No SSO provider found."),
+            Error::InvalidCallbackUrl => write!(f, "Invalid callback url."),
+            Error::RedirectTimeout => write!(f, "Timed out waiting for the redirect."),
+            Error::RateLimited => write!(f, "Too many requests, try again later."),
+            Error::NoPasswordRecovery => write!(f, "No password recovery in progress."),
+            Error::UnsupportedServerVersion { version, required } => write!(
+                f,
+                "GoTrue {} is not supported, {} or newer is required.",
+                version, required
+            ),
+            Error::CallbackError {
+                error,
+                description: Some(description),
+                ..
+            } => write!(f, "{}: {}", error, description),
+            Error::CallbackError { error, .. } => write!(f, "{}", error),
             Error::InternalError => write!(f, "GoTrue internal error"),
         }
     }
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+/// The health status of a GoTrue server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Health {
+    /// The release of the server, e.g. `v2.164.0`.
+    pub version: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// Parses a GoTrue version like `v2.164.0` into its major, minor and patch numbers. The `v`
+/// prefix is optional and pre-release or build suffixes are ignored. Returns `None` for
+/// versions that aren't releases, e.g. `unspecified` on development builds.
+pub(crate) fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?
+        .split('.')
+        .map(|part| part.parse::<u64>().ok());
+
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
@@ -0,0 +1,12 @@
+use crate::provider::Provider;
+
+/// An ID token issued by an OIDC provider, e.g. from the native Google or Apple sign in SDKs.
+#[derive(Debug, Clone)]
+pub struct IdTokenCredentials {
+    pub provider: Provider,
+    pub id_token: String,
+    /// Required if the ID token contains an `at_hash` claim.
+    pub access_token: Option<String>,
+    /// Required if the ID token contains a `nonce` claim. This is the raw nonce, not the hash.
+    pub nonce: Option<String>,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// An identity of a user, e.g. their email address or a linked OAuth account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Identity {
+    /// The id used to unlink the identity.
+    pub identity_id: String,
+    /// The id of the user at the provider.
+    pub id: String,
+    pub user_id: String,
+    pub identity_data: Option<Value>,
+    pub provider: String,
+    pub email: Option<String>,
+    pub last_sign_in_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
@@ -23,7 +23,7 @@
 //!     let email = "email@example.com".to_string();
 //!     let password = "Abcd1234!";
 //!
-//!     let session = client.sign_up(EmailOrPhone::Email(email), password).await;
+//!     let session = client.sign_up(EmailOrPhone::Email(email), password, None).await;
 //!
 //!     println!("{:?}", session);
 //! }
@@ -34,16 +34,63 @@
 //! [gotrue]: https://github.com/supabase/gotrue
 //! [readme]: https://github.com/fubinator/gotrue-rs
 
+mod admin_user_attributes;
+mod anonymous_sign_in_options;
 mod api;
+mod authorize_url_builder;
+mod channel;
 mod client;
+mod email_change_options;
+mod email_change_outcome;
+mod email_change_request;
 pub mod error;
+mod health;
+mod id_token_credentials;
+mod identity;
+mod loopback;
+pub mod mfa;
+mod oauth_options;
+mod pkce;
+mod provider;
+mod resend_options;
+mod reset_password_options;
 mod session;
+mod settings;
+mod sign_out_scope;
+mod sign_up_options;
+mod sign_up_outcome;
 mod user;
 mod user_attributes;
 mod user_list;
 mod user_update;
+mod verify_otp_params;
 
+pub use admin_user_attributes::AdminUserAttributes;
+pub use anonymous_sign_in_options::AnonymousSignInOptions;
 pub use api::Api;
+pub use api::DomainOrProviderId;
 pub use api::EmailOrPhone;
+pub use authorize_url_builder::AuthorizeUrlBuilder;
+pub use channel::Channel;
 pub use client::Client;
+pub use email_change_options::EmailChangeOptions;
+pub use email_change_outcome::EmailChangeOutcome;
+pub use email_change_request::{EmailChangeRequest, PendingConfirmations};
+pub use health::Health;
+pub use id_token_credentials::IdTokenCredentials;
+pub use identity::Identity;
+pub use loopback::LoopbackListener;
+pub use oauth_options::OAuthOptions;
+pub use pkce::Pkce;
+pub use provider::Provider;
+pub use resend_options::{ResendOptions, ResendType};
+pub use reset_password_options::ResetPasswordOptions;
+pub use session::Session;
+pub use settings::Settings;
+pub use sign_out_scope::SignOutScope;
+pub use sign_up_options::SignUpOptions;
+pub use sign_up_outcome::SignUpOutcome;
+pub use user::User;
 pub use user_attributes::UserAttributes;
+pub use user_list::{ListUsersOptions, UserList};
+pub use verify_otp_params::{EmailOtpType, MobileOtpType, VerifyOtpParams};
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time,
+};
+use url::Url;
+
+use crate::error::Error;
+
+const CALLBACK_PATH: &str = "/callback";
+
+/// How long the user has to finish signing in with the provider by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How long a connection may take to send its request. Browsers open idle connections in
+/// advance, so this keeps them from lingering.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SUCCESS_PAGE: &str = "<html><body>Signed in. You can close this window now.</body></html>";
+const ERROR_PAGE: &str = "<html><body>Sign in failed. You can close this window now.</body></html>";
+
+/// A temporary HTTP listener on 127.0.0.1 that receives the OAuth redirect for CLI and
+/// desktop apps. Its `redirect_url` has to be on the server's redirect allow-list, such as
+/// `GOTRUE_URI_ALLOW_LIST=http://127.0.0.1:*/callback`.
+///
+/// # Example
+///
+/// ```no_run
+/// use go_true::{Api, LoopbackListener, Pkce, Provider};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = Api::new("http://localhost:9998");
+///     let listener = LoopbackListener::bind(0).await?;
+///     let pkce = Pkce::generate();
+///
+///     let url = client
+///         .authorize_url(Provider::Github)
+///         .redirect_to(listener.redirect_url())
+///         .pkce(&pkce)
+///         .build();
+///
+///     // open `url` in the browser
+///
+///     let code = listener.wait_for_code().await?;
+///     let session = client
+///         .exchange_code_for_session(code, &pkce.code_verifier)
+///         .await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct LoopbackListener {
+    listener: TcpListener,
+    redirect_url: String,
+    timeout: Duration,
+}
+
+impl LoopbackListener {
+    /// Binds the listener to the given port on 127.0.0.1. Port 0 picks a free port.
+    pub async fn bind(port: u16) -> std::io::Result<LoopbackListener> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        let redirect_url = format!(
+            "http://127.0.0.1:{}{}",
+            listener.local_addr()?.port(),
+            CALLBACK_PATH
+        );
+
+        Ok(LoopbackListener {
+            listener,
+            redirect_url,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Sets how long `wait_for_code` waits for the redirect, 5 minutes by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The url that has to be used as `redirect_to`.
+    pub fn redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    /// Waits for the redirect and returns the auth code from it. Requests to other paths,
+    /// like the browser asking for a favicon, are answered with 404 and ignored. Fails with
+    /// `Error::RedirectTimeout` if no redirect arrives in time.
+    pub async fn wait_for_code(self) -> Result<String, Error> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let listener = self.listener;
+
+        let accept = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream, sender.clone()));
+            }
+        });
+
+        let result = time::timeout(self.timeout, receiver.recv()).await;
+        accept.abort();
+
+        match result {
+            Ok(Some(result)) => result,
+            Ok(None) => Err(Error::InternalError),
+            Err(_) => Err(Error::RedirectTimeout),
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, sender: mpsc::Sender<Result<String, Error>>) {
+    let path = match time::timeout(READ_TIMEOUT, read_request_path(&mut stream)).await {
+        Ok(Some(path)) => path,
+        _ => return,
+    };
+
+    let url = match Url::parse(&format!("http://127.0.0.1{}", path)) {
+        Ok(url) if url.path() == CALLBACK_PATH => url,
+        _ => {
+            respond(&mut stream, "404 Not Found", "").await;
+            return;
+        }
+    };
+
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    let result = if let Some(code) = params.get("code") {
+        respond(&mut stream, "200 OK", SUCCESS_PAGE).await;
+        Ok(code.clone())
+    } else {
+        respond(&mut stream, "400 Bad Request", ERROR_PAGE).await;
+
+        match params.get("error") {
+            Some(error) => Err(Error::CallbackError {
+                error: error.clone(),
+                error_code: params.get("error_code").cloned(),
+                description: params.get("error_description").cloned(),
+            }),
+            None => Err(Error::InvalidCallbackUrl),
+        }
+    };
+
+    let _ = sender.send(result).await;
+}
+
+async fn read_request_path(stream: &mut TcpStream) -> Option<String> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 || request.len() > 16 * 1024 {
+            return None;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next()?.split_whitespace();
+
+    match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(path)) => Some(path.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
@@ -0,0 +1,139 @@
+//! Types for multi-factor authentication.
+
+use serde::Deserialize;
+
+use crate::session::Session;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorType {
+    Totp,
+    Phone,
+    /// A factor type this crate doesn't know yet, e.g. `webauthn`.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorStatus {
+    Verified,
+    Unverified,
+    #[serde(other)]
+    Unknown,
+}
+
+/// A factor enrolled by a user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Factor {
+    pub id: String,
+    pub friendly_name: Option<String>,
+    pub factor_type: FactorType,
+    pub status: FactorStatus,
+    /// The phone number of a phone factor.
+    pub phone: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Parameters for enrolling a new factor.
+#[derive(Debug, Clone)]
+pub enum EnrollParams {
+    Totp {
+        friendly_name: Option<String>,
+        /// Shown in the authenticator app, defaults to the GoTrue site url.
+        issuer: Option<String>,
+    },
+    Phone {
+        friendly_name: Option<String>,
+        /// The phone number the codes are sent to, in E.164 format.
+        phone: String,
+    },
+}
+
+/// The secret of a newly enrolled TOTP factor.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TotpEnrollment {
+    /// An SVG image of the QR code that can be scanned by authenticator apps.
+    pub qr_code: String,
+    pub secret: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnrollResponse {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub factor_type: FactorType,
+    pub friendly_name: Option<String>,
+    /// Only set for TOTP factors.
+    pub totp: Option<TotpEnrollment>,
+    /// Only set for phone factors.
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Challenge {
+    pub id: String,
+    /// Unix timestamp after which the challenge can no longer be verified.
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssuranceLevel {
+    Aal1,
+    Aal2,
+}
+
+/// A method the user authenticated with in the current session, e.g. `password` or `totp`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthenticationMethod {
+    pub method: String,
+    /// Unix timestamp of the authentication.
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthenticatorAssuranceLevel {
+    /// The level of the current session, `None` if the access token has no `aal` claim.
+    pub current_level: Option<AssuranceLevel>,
+    /// The level the user can reach, `Aal2` as soon as a verified factor exists.
+    pub next_level: Option<AssuranceLevel>,
+    pub current_authentication_methods: Vec<AuthenticationMethod>,
+}
+
+impl AuthenticatorAssuranceLevel {
+    /// Reads the assurance level from the claims of the session's access token and the
+    /// verified factors of its user. Returns `None` if the access token can't be decoded.
+    pub(crate) fn from_session(session: &Session) -> Option<AuthenticatorAssuranceLevel> {
+        #[derive(Deserialize)]
+        struct Claims {
+            aal: Option<AssuranceLevel>,
+            #[serde(default)]
+            amr: Vec<AuthenticationMethod>,
+        }
+
+        let payload = session.access_token.split('.').nth(1)?;
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).ok()?;
+        let claims: Claims = serde_json::from_slice(&payload).ok()?;
+
+        let has_verified_factor = session
+            .user
+            .factors
+            .iter()
+            .any(|factor| factor.status == FactorStatus::Verified);
+
+        let next_level = if has_verified_factor {
+            Some(AssuranceLevel::Aal2)
+        } else {
+            claims.aal
+        };
+
+        Some(AuthenticatorAssuranceLevel {
+            current_level: claims.aal,
+            next_level,
+            current_authentication_methods: claims.amr,
+        })
+    }
+}
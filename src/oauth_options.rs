@@ -0,0 +1,10 @@
+/// Optional parameters for OAuth flows.
+#[derive(Debug, Clone, Default)]
+pub struct OAuthOptions {
+    /// Where the user is redirected to after signing in with the provider.
+    pub redirect_to: Option<String>,
+    /// Space separated scopes requested from the provider.
+    pub scopes: Option<String>,
+    /// Additional query parameters passed on to the provider, e.g. `access_type=offline`.
+    pub query_params: Vec<(String, String)>,
+}
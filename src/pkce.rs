@@ -0,0 +1,42 @@
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+/// The code challenge method sent to GoTrue alongside the challenge.
+pub const CODE_CHALLENGE_METHOD: &str = "s256";
+
+/// A PKCE code verifier and the matching S256 code challenge.
+#[derive(Debug, Clone)]
+pub struct Pkce {
+    pub code_verifier: String,
+    pub code_challenge: String,
+}
+
+impl Pkce {
+    /// Generates a random code verifier and derives its code challenge.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use go_true::Pkce;
+    ///
+    /// let pkce = Pkce::generate();
+    /// assert_eq!(pkce.code_verifier.len(), 64);
+    /// ```
+    pub fn generate() -> Pkce {
+        let code_verifier: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(64)
+            .map(char::from)
+            .collect();
+
+        let code_challenge = base64::encode_config(
+            Sha256::digest(code_verifier.as_bytes()),
+            base64::URL_SAFE_NO_PAD,
+        );
+
+        Pkce {
+            code_verifier,
+            code_challenge,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// An OAuth provider supported by GoTrue.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Provider {
+    Apple,
+    Azure,
+    Bitbucket,
+    Discord,
+    Facebook,
+    Figma,
+    Github,
+    Gitlab,
+    Google,
+    Kakao,
+    Keycloak,
+    LinkedinOidc,
+    Notion,
+    SlackOidc,
+    Spotify,
+    Twitch,
+    Twitter,
+    Workos,
+    Zoom,
+    /// A provider that is not built in, e.g. a custom OIDC provider.
+    Custom(String),
+}
+
+impl Provider {
+    /// Returns the name GoTrue uses for the provider.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Provider::Apple => "apple",
+            Provider::Azure => "azure",
+            Provider::Bitbucket => "bitbucket",
+            Provider::Discord => "discord",
+            Provider::Facebook => "facebook",
+            Provider::Figma => "figma",
+            Provider::Github => "github",
+            Provider::Gitlab => "gitlab",
+            Provider::Google => "google",
+            Provider::Kakao => "kakao",
+            Provider::Keycloak => "keycloak",
+            Provider::LinkedinOidc => "linkedin_oidc",
+            Provider::Notion => "notion",
+            Provider::SlackOidc => "slack_oidc",
+            Provider::Spotify => "spotify",
+            Provider::Twitch => "twitch",
+            Provider::Twitter => "twitter",
+            Provider::Workos => "workos",
+            Provider::Zoom => "zoom",
+            Provider::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
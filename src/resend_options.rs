@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+/// The kind of confirmation or OTP that is sent again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResendType {
+    Signup,
+    EmailChange,
+    Sms,
+    PhoneChange,
+}
+
+/// Optional parameters for resending a confirmation or OTP.
+#[derive(Debug, Clone, Default)]
+pub struct ResendOptions {
+    /// Where the user is redirected to after confirming their email address.
+    pub email_redirect_to: Option<String>,
+    pub captcha_token: Option<String>,
+}
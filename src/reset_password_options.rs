@@ -0,0 +1,10 @@
+/// Optional parameters for sending a password recovery email.
+#[derive(Debug, Clone, Default)]
+pub struct ResetPasswordOptions {
+    /// Where the user is redirected to from the recovery link.
+    pub redirect_to: Option<String>,
+    pub captcha_token: Option<String>,
+    /// The challenge of a [`Pkce`](crate::Pkce). The recovery link then redirects with a
+    /// code that is exchanged for a session with `exchange_code_for_session`.
+    pub code_challenge: Option<String>,
+}
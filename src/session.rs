@@ -8,5 +8,11 @@ pub struct Session {
     pub token_type: String,
     pub expires_in: i32,
     pub refresh_token: String,
+    /// Unix timestamp at which the access token expires.
+    pub expires_at: Option<i64>,
+    /// The OAuth provider's access token, only set right after an OAuth sign in.
+    pub provider_token: Option<String>,
+    /// The OAuth provider's refresh token, only set right after an OAuth sign in.
+    pub provider_refresh_token: Option<String>,
     pub user: User,
 }
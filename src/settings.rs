@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// The features enabled on a GoTrue server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Settings {
+    /// Whether each external OAuth provider is enabled, keyed by provider name.
+    pub external: HashMap<String, bool>,
+    pub disable_signup: bool,
+    pub mailer_autoconfirm: bool,
+    pub phone_autoconfirm: bool,
+    /// The SMS provider, empty if none is configured.
+    #[serde(default)]
+    pub sms_provider: String,
+    #[serde(default)]
+    pub saml_enabled: bool,
+}
@@ -0,0 +1,14 @@
+use serde::Serialize;
+
+/// Which sessions of the user are revoked on sign out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignOutScope {
+    /// All sessions of the user.
+    #[default]
+    Global,
+    /// Only the session the access token belongs to.
+    Local,
+    /// All sessions except the one the access token belongs to.
+    Others,
+}
@@ -0,0 +1,15 @@
+use serde_json::Value;
+
+use crate::channel::Channel;
+
+/// Optional parameters for a sign up.
+#[derive(Debug, Clone, Default)]
+pub struct SignUpOptions {
+    /// Stored as the user's `user_metadata`.
+    pub data: Option<Value>,
+    /// Where the user is redirected to after confirming their email address.
+    pub email_redirect_to: Option<String>,
+    pub captcha_token: Option<String>,
+    /// The channel used to send the confirmation OTP when signing up with a phone number.
+    pub channel: Option<Channel>,
+}
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+use crate::{session::Session, user::User};
+
+/// The result of a sign up. GoTrue only issues a session right away when the user does not
+/// have to confirm their email address or phone number first.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SignUpOutcome {
+    Session(Session),
+    ConfirmationRequired(User),
+}
+
+impl SignUpOutcome {
+    /// Returns the signed up user.
+    pub fn user(&self) -> &User {
+        match self {
+            SignUpOutcome::Session(session) => &session.user,
+            SignUpOutcome::ConfirmationRequired(user) => user,
+        }
+    }
+
+    /// Returns the session if the user was signed in right away.
+    pub fn session(&self) -> Option<&Session> {
+        match self {
+            SignUpOutcome::Session(session) => Some(session),
+            SignUpOutcome::ConfirmationRequired(_) => None,
+        }
+    }
+}
@@ -1,4 +1,7 @@
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{identity::Identity, mfa::Factor};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct User {
@@ -9,6 +12,13 @@ pub struct User {
     pub email_confirmed_at: Option<String>,
     pub phone: String,
     pub last_sign_in_at: Option<String>,
+    pub app_metadata: Value,
+    pub user_metadata: Value,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub is_anonymous: bool,
+    pub identities: Option<Vec<Identity>>,
+    #[serde(default)]
+    pub factors: Vec<Factor>,
 }
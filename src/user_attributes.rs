@@ -1,8 +1,16 @@
 use serde_json::Value;
 
-#[derive(Debug)]
+/// Attributes for updating the current user. Only the fields that are set are changed.
+///
+/// An anonymous user is converted into a permanent user by first setting an email address or
+/// phone number and confirming it. Only a confirmed user can set a password.
+#[derive(Debug, Default)]
 pub struct UserAttributes {
-    pub email: String,
-    pub password: String,
-    pub data: Value,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub password: Option<String>,
+    pub data: Option<Value>,
+    /// The reauthentication nonce, required to change the password when secure password
+    /// change is enabled.
+    pub nonce: Option<String>,
 }
@@ -1,7 +1,54 @@
 use crate::user::User;
 use serde::Deserialize;
 
+/// A page of users from the admin API.
 #[derive(Debug, Clone, Deserialize)]
 pub struct UserList {
     pub users: Vec<User>,
+    /// The number of users on all pages, from the `X-Total-Count` header.
+    #[serde(skip)]
+    pub total: Option<u64>,
+    /// The next page, `None` on the last page.
+    #[serde(skip)]
+    pub next_page: Option<u32>,
+    #[serde(skip)]
+    pub last_page: Option<u32>,
+}
+
+/// Optional parameters for listing users.
+#[derive(Debug, Clone, Default)]
+pub struct ListUsersOptions {
+    /// The page to get, starting at 1.
+    pub page: Option<u32>,
+    /// The number of users per page, GoTrue defaults to 50.
+    pub per_page: Option<u32>,
+}
+
+/// Reads the page numbers of the `next` and `last` links from a `Link` header like
+/// `</admin/users?page=2&per_page=50>; rel="next", </admin/users?page=4&per_page=50>; rel="last"`.
+pub(crate) fn parse_link_header(header: &str) -> (Option<u32>, Option<u32>) {
+    let mut next_page = None;
+    let mut last_page = None;
+
+    for link in header.split(',') {
+        let mut parts = link.split(';');
+        let target = parts.next().unwrap_or_default().trim();
+        let target = target.trim_start_matches('<').trim_end_matches('>');
+
+        let page = target.split_once('?').and_then(|(_, query)| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "page")
+                .and_then(|(_, page)| page.parse().ok())
+        });
+
+        for parameter in parts {
+            match parameter.trim() {
+                "rel=\"next\"" => next_page = page,
+                "rel=\"last\"" => last_page = page,
+                _ => {}
+            }
+        }
+    }
+
+    (next_page, last_page)
 }
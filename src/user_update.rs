@@ -4,8 +4,12 @@ use serde::Deserialize;
 pub struct UserUpdate {
     pub id: String,
     pub email: String,
-    pub new_email: String,
-    pub email_change_sent_at: String,
+    /// The requested email address while the change is not confirmed yet.
+    pub new_email: Option<String>,
+    pub email_change_sent_at: Option<String>,
+    /// The requested phone number while the change is not confirmed yet.
+    pub new_phone: Option<String>,
+    pub phone_change_sent_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+/// OTP types that are sent to a phone number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MobileOtpType {
+    Sms,
+    PhoneChange,
+}
+
+/// OTP types that are sent to an email address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailOtpType {
+    Email,
+    Signup,
+    Invite,
+    Magiclink,
+    Recovery,
+    EmailChange,
+}
+
+/// Parameters for verifying an OTP, either by the token sent to a phone or email address
+/// or by the token hash from an email link.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum VerifyOtpParams {
+    Mobile {
+        phone: String,
+        token: String,
+        #[serde(rename = "type")]
+        otp_type: MobileOtpType,
+    },
+    Email {
+        email: String,
+        token: String,
+        #[serde(rename = "type")]
+        otp_type: EmailOtpType,
+    },
+    TokenHash {
+        token_hash: String,
+        #[serde(rename = "type")]
+        otp_type: EmailOtpType,
+    },
+}
@@ -1,4 +1,11 @@
-use go_true::{Api, EmailOrPhone, UserAttributes};
+use futures::TryStreamExt;
+use go_true::{
+    mfa::{EnrollParams, FactorStatus, FactorType},
+    AdminUserAttributes, AnonymousSignInOptions, Api, Channel, EmailChangeOptions, EmailOrPhone,
+    EmailOtpType, ListUsersOptions, OAuthOptions, PendingConfirmations, Pkce, Provider, ResendType,
+    ResetPasswordOptions, SignOutScope, SignUpOptions, SignUpOutcome, User, UserAttributes,
+    VerifyOtpParams,
+};
 use rand::{distributions::Alphanumeric, Rng};
 use serde_json::json;
 use std::error::Error;
@@ -6,24 +13,17 @@ use std::error::Error;
 use hmac::{Hmac, Mac};
 use jwt::SignWithKey;
 use sha2::Sha256;
-use std::collections::BTreeMap;
-
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
-
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct AdminUserAttributes {
-    pub email: String,
-    pub password: Option<String>,
-    pub data: Option<Value>,
-    pub email_confirmed: Option<bool>,
-    pub phone_confirmed: Option<bool>,
-}
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use url::Url;
 
 fn get_api_client() -> Api {
     let api: Api = Api::new("http://localhost:9998");
 
-    return api;
+    api
 }
 
 fn get_service_api_client() -> Api {
@@ -36,7 +36,64 @@ fn get_service_api_client() -> Api {
     let api: Api = Api::new("http://localhost:9998")
         .insert_header("Authorization", format!("Bearer {token_str}"));
 
-    return api;
+    api
+}
+
+/// Starts a stand-in admin API that answers `/admin/users` with the users of the requested
+/// page, starting at 1, and that page's `Link` header.
+async fn start_user_list_server(pages: Vec<(Vec<&'static str>, Option<&'static str>)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let total: usize = pages.iter().map(|(ids, _)| ids.len()).sum();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 4096];
+            let read = stream.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let url = Url::parse(&format!("http://localhost{path}")).unwrap();
+
+            let page: usize = url
+                .query_pairs()
+                .find(|(key, _)| key == "page")
+                .and_then(|(_, page)| page.parse().ok())
+                .unwrap_or(1);
+            let (ids, link) = pages.get(page - 1).cloned().unwrap_or_default();
+
+            let users: Vec<_> = ids
+                .iter()
+                .map(|id| {
+                    json!({
+                        "id": id,
+                        "email": format!("{id}@example.com"),
+                        "aud": "authenticated",
+                        "role": "authenticated",
+                        "phone": "",
+                        "app_metadata": {},
+                        "user_metadata": {},
+                        "created_at": "2024-01-01T00:00:00Z",
+                        "updated_at": "2024-01-01T00:00:00Z"
+                    })
+                })
+                .collect();
+            let body = json!({ "aud": "authenticated", "users": users }).to_string();
+            let link = link
+                .map(|link| format!("Link: {link}\r\n"))
+                .unwrap_or_default();
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nX-Total-Count: {total}\r\n{link}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+
+    address
 }
 
 fn get_random_email() -> String {
@@ -47,7 +104,7 @@ fn get_random_email() -> String {
         .map(char::from)
         .collect();
 
-    return format!("{random_string}@example.com");
+    format!("{random_string}@example.com")
 }
 
 #[tokio::test]
@@ -57,10 +114,51 @@ async fn it_signs_up_with_email() -> Result<(), Box<dyn Error>> {
 
     let api = get_api_client();
     let res = api
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
 
-    assert_eq!(res.user.email, email);
+    match res {
+        SignUpOutcome::Session(session) => assert_eq!(session.user.email, email),
+        SignUpOutcome::ConfirmationRequired(_) => panic!("Should return a session"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_signs_up_with_options() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+    let options = SignUpOptions {
+        data: Some(json!({ "name": "Test" })),
+        ..Default::default()
+    };
+
+    let api = get_api_client();
+    let res = api
+        .sign_up(EmailOrPhone::Email(email.clone()), password, Some(options))
+        .await?;
+
+    assert_eq!(res.user().email, email);
+    assert_eq!(res.user().user_metadata["name"], "Test");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_signs_up_with_email_confirmation_required() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = Api::new("http://localhost:9999");
+    let res = api
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    match res {
+        SignUpOutcome::Session(_) => panic!("Should require confirmation"),
+        SignUpOutcome::ConfirmationRequired(user) => assert_eq!(user.email, email),
+    }
 
     Ok(())
 }
@@ -71,7 +169,7 @@ async fn it_signs_in_with_email() -> Result<(), Box<dyn Error>> {
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let res = api
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -81,19 +179,35 @@ async fn it_signs_in_with_email() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn it_signs_in_anonymously() -> Result<(), Box<dyn Error>> {
+    let api = get_api_client();
+    let options = AnonymousSignInOptions {
+        data: Some(json!({ "cart": "guest" })),
+        ..Default::default()
+    };
+
+    let session = api.sign_in_anonymously(Some(options)).await?;
+
+    assert!(session.user.is_anonymous);
+    assert_eq!(session.user.user_metadata["cart"], "guest");
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn it_send_magic_link_with_valid_email() -> Result<(), Box<dyn Error>> {
     let email = get_random_email();
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let res = api
         .send_otp(EmailOrPhone::Email(email.clone()), None)
         .await?;
 
-    assert_eq!(res, true);
+    assert!(res);
 
     Ok(())
 }
@@ -104,10 +218,48 @@ async fn it_does_not_send_magic_link_with_invalid_email() -> Result<(), Box<dyn
     let api = get_api_client();
     let response = api.send_otp(EmailOrPhone::Email(email), None).await;
 
-    match response {
-        Ok(_) => panic!("Should not work"),
-        Err(_) => assert!(true),
-    }
+    assert!(response.is_err(), "Should not work");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_resend_signup_confirmation() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = Api::new("http://localhost:9999");
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let res = api
+        .resend(ResendType::Signup, EmailOrPhone::Email(email), None)
+        .await?;
+
+    assert!(res);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_not_verify_wrong_otp() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    api.send_otp(EmailOrPhone::Email(email.clone()), None)
+        .await?;
+
+    let params = VerifyOtpParams::Email {
+        email,
+        token: "000000".to_string(),
+        otp_type: EmailOtpType::Magiclink,
+    };
+    let response = api.verify_otp(params).await;
+
+    assert!(response.is_err(), "Should not work");
 
     Ok(())
 }
@@ -118,7 +270,7 @@ async fn it_should_log_out() -> Result<(), Box<dyn Error>> {
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let res = api
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -126,9 +278,34 @@ async fn it_should_log_out() -> Result<(), Box<dyn Error>> {
 
     assert_eq!(res.user.email, email);
 
-    let success = api.sign_out(&res.access_token).await?;
+    let success = api.sign_out(&res.access_token, None).await?;
 
-    assert_eq!(success, true);
+    assert!(success);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_log_out_other_sessions() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let first = api
+        .sign_in(EmailOrPhone::Email(email.clone()), password)
+        .await?;
+    let second = api.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    api.sign_out(&first.access_token, Some(SignOutScope::Others))
+        .await?;
+
+    assert!(api.refresh_access_token(&first.refresh_token).await.is_ok());
+    assert!(api
+        .refresh_access_token(&second.refresh_token)
+        .await
+        .is_err());
 
     Ok(())
 }
@@ -139,7 +316,7 @@ async fn it_should_return_error_if_token_is_invalid() -> Result<(), Box<dyn Erro
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let res = api
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -147,12 +324,9 @@ async fn it_should_return_error_if_token_is_invalid() -> Result<(), Box<dyn Erro
 
     assert_eq!(res.user.email, email);
 
-    let success = api.sign_out("invalid-token").await;
+    let success = api.sign_out("invalid-token", None).await;
 
-    match success {
-        Ok(_) => panic!("Should not work"),
-        Err(_) => assert!(true),
-    }
+    assert!(success.is_err(), "Should not work");
     Ok(())
 }
 
@@ -162,21 +336,102 @@ async fn it_should_send_password_recovery_email() -> Result<(), Box<dyn Error>>
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let success = api.reset_password_for_email(&email, None).await?;
+    assert!(success);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_send_password_recovery_email_with_pkce() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
 
-    let success = api.reset_password_for_email(&email).await?;
-    assert_eq!(success, true);
+    let pkce = Pkce::generate();
+    let options = ResetPasswordOptions {
+        redirect_to: Some("http://localhost:3000/reset".to_string()),
+        code_challenge: Some(pkce.code_challenge),
+        ..Default::default()
+    };
+    let success = api.reset_password_for_email(&email, Some(options)).await?;
 
+    assert!(success);
     Ok(())
 }
 
 #[test]
 fn it_should_return_url_for_provider() {
     let api = get_api_client();
-    let url = api.get_url_for_provider("Github");
+    let url = api.get_url_for_provider(Provider::Github);
 
-    assert!(url.ends_with("/authorize?provider=Github"));
+    assert!(url.ends_with("/authorize?provider=github"));
+}
+
+#[test]
+fn it_should_return_url_for_custom_provider() {
+    let api = get_api_client();
+    let url = api.get_url_for_provider(Provider::Custom("my-oidc".to_string()));
+
+    assert!(url.ends_with("/authorize?provider=my-oidc"));
+}
+
+#[test]
+fn it_should_return_url_for_provider_with_pkce() {
+    let api = get_api_client();
+    let (url, pkce) = api.get_url_for_provider_with_pkce(Provider::Github);
+
+    assert!(url.contains("/authorize?provider=github"));
+    assert!(url.contains(&format!("code_challenge={}", pkce.code_challenge)));
+    assert!(url.ends_with("code_challenge_method=s256"));
+    assert_ne!(pkce.code_verifier, pkce.code_challenge);
+}
+
+#[test]
+fn it_should_build_encoded_authorize_url() {
+    let api = get_api_client();
+    let pkce = Pkce::generate();
+    let url = api
+        .authorize_url(Provider::Google)
+        .redirect_to("https://example.com/callback?next=/home")
+        .scopes("email profile")
+        .query_param("access_type", "offline")
+        .query_param("prompt", "consent")
+        .pkce(&pkce)
+        .build();
+
+    assert_eq!(
+        url,
+        format!(
+            "http://localhost:9998/authorize?provider=google\
+             &redirect_to=https%3A%2F%2Fexample.com%2Fcallback%3Fnext%3D%2Fhome\
+             &scopes=email+profile\
+             &code_challenge={}&code_challenge_method=s256\
+             &access_type=offline&prompt=consent",
+            pkce.code_challenge
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_should_not_exchange_invalid_auth_code() -> Result<(), Box<dyn Error>> {
+    let api = get_api_client();
+    let (_, pkce) = api.get_url_for_provider_with_pkce(Provider::Github);
+
+    let result = api
+        .exchange_code_for_session("invalid-auth-code", &pkce.code_verifier)
+        .await;
+
+    let status = result.unwrap_err().status();
+    assert!(status.is_some_and(|s| s.is_client_error()), "{status:?}");
+
+    Ok(())
 }
 
 #[tokio::test]
@@ -185,7 +440,7 @@ async fn it_should_refresh_token() -> Result<(), Box<dyn Error>> {
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let session = api
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -204,7 +459,7 @@ async fn it_should_return_user() -> Result<(), Box<dyn Error>> {
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let session = api
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -223,20 +478,177 @@ async fn it_should_update_user() -> Result<(), Box<dyn Error>> {
     let password = "Abcd1234!";
 
     let api = get_api_client();
-    api.sign_up(EmailOrPhone::Email(email.clone()), password)
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
 
     let new_email = get_random_email();
     let attributes = UserAttributes {
-        email: new_email.clone(),
-        password: "Abcd12345!".to_string(),
-        data: json!({ "test": "test" }),
+        email: Some(new_email.clone()),
+        password: Some("Abcd12345!".to_string()),
+        data: Some(json!({ "test": "test" })),
+        ..Default::default()
     };
 
     let update = api.update_user(attributes, &session.access_token).await?;
 
-    assert_eq!(update.new_email, new_email);
+    assert_eq!(update.new_email, Some(new_email));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_reauthenticate() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let success = api.reauthenticate(&session.access_token).await?;
+
+    assert!(success);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_get_link_identity_url() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let options = OAuthOptions {
+        redirect_to: Some("http://localhost:3000/linked".to_string()),
+        scopes: Some("https://www.googleapis.com/auth/calendar".to_string()),
+        query_params: vec![("access_type".to_string(), "offline".to_string())],
+    };
+    let url = api
+        .link_identity(&session.access_token, Provider::Google, Some(options))
+        .await?;
+
+    let url = Url::parse(&url)?;
+    let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+    assert_eq!(url.host_str(), Some("accounts.google.com"));
+    assert_eq!(
+        params.get("redirect_uri").map(String::as_str),
+        Some("http://localhost:9998/callback")
+    );
+    assert!(params["scope"].contains("https://www.googleapis.com/auth/calendar"));
+    assert_eq!(
+        params.get("access_type").map(String::as_str),
+        Some("offline")
+    );
+    assert!(params.contains_key("state"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_not_get_link_identity_url_without_valid_token() -> Result<(), Box<dyn Error>> {
+    let result = get_api_client()
+        .link_identity("invalid-token", Provider::Google, None)
+        .await;
+
+    assert_eq!(result.unwrap_err().status().map(|s| s.as_u16()), Some(401));
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_not_unlink_unknown_identity() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let result = api
+        .unlink_identity(
+            &session.access_token,
+            "00000000-0000-0000-0000-000000000000",
+        )
+        .await;
+
+    assert_eq!(result.unwrap_err().status().map(|s| s.as_u16()), Some(422));
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_enroll_and_unenroll_totp_factor() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let params = EnrollParams::Totp {
+        friendly_name: Some("Authenticator".to_string()),
+        issuer: None,
+    };
+    let factor = api.mfa_enroll(&session.access_token, params).await?;
+
+    assert_eq!(factor.factor_type, FactorType::Totp);
+    assert!(factor.totp.unwrap().uri.starts_with("otpauth://totp/"));
+
+    let factors = api.mfa_list_factors(&session.access_token).await?;
+    assert!(factors.iter().any(|f| f.id == factor.id));
+
+    let challenge = api
+        .mfa_challenge(&session.access_token, &factor.id, None)
+        .await?;
+    let verify = api
+        .mfa_verify(&session.access_token, &factor.id, &challenge.id, "000000")
+        .await;
+    assert!(verify.is_err(), "Should not work");
+
+    api.mfa_unenroll(&session.access_token, &factor.id).await?;
+
+    let factors = api.mfa_list_factors(&session.access_token).await?;
+    assert!(factors.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_enroll_and_verify_phone_factor() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let api = get_api_client();
+    api.sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let params = EnrollParams::Phone {
+        friendly_name: None,
+        phone: "+4915112345678".to_string(),
+    };
+    let factor = api.mfa_enroll(&session.access_token, params).await?;
+
+    assert_eq!(factor.factor_type, FactorType::Phone);
+    assert_eq!(factor.phone.as_deref(), Some("4915112345678"));
+
+    let challenge = api
+        .mfa_challenge(&session.access_token, &factor.id, Some(Channel::Sms))
+        .await?;
+    let session = api
+        .mfa_verify(&session.access_token, &factor.id, &challenge.id, "123456")
+        .await?;
+
+    let factors = api.mfa_list_factors(&session.access_token).await?;
+    let factor = factors.iter().find(|f| f.id == factor.id).unwrap();
+    assert_eq!(factor.status, FactorStatus::Verified);
 
     Ok(())
 }
@@ -252,19 +664,251 @@ async fn it_should_invite_user_by_email() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn it_should_request_email_change() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    get_api_client()
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let api = Api::new("http://localhost:9999");
+    let session = api
+        .sign_in(EmailOrPhone::Email(email.clone()), password)
+        .await?;
+
+    let new_email = get_random_email();
+    let request = api
+        .request_email_change(
+            &session.access_token,
+            &new_email,
+            Some(EmailChangeOptions {
+                email_redirect_to: Some("http://localhost:3000/email-changed".to_string()),
+                secure_email_change: Some(true),
+            }),
+        )
+        .await?;
+
+    assert_eq!(request.user.email, email);
+    assert_eq!(request.user.new_email, Some(new_email));
+    assert!(request.user.email_change_sent_at.is_some());
+    assert_eq!(request.pending, PendingConfirmations::BothAddresses);
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_not_verify_email_change_with_invalid_token() -> Result<(), Box<dyn Error>> {
+    let result = get_api_client()
+        .verify_email_change(get_random_email(), "000000")
+        .await;
+
+    let status = result.unwrap_err().status();
+    assert!(status.is_some_and(|s| s.is_client_error()), "{status:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_change_phone() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    get_api_client()
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    // The number has a fixed OTP on the server without SMS autoconfirm
+    let phone = "4915112345679";
+    let api = Api::new("http://localhost:9999");
+    let session = api.sign_in(EmailOrPhone::Email(email), password).await?;
+    let update = api
+        .request_phone_change(&session.access_token, format!("+{phone}"), None)
+        .await?;
+
+    assert_eq!(update.new_phone.as_deref(), Some(phone));
+    assert!(update.phone_change_sent_at.is_some());
+
+    let result = api.verify_phone_change(format!("+{phone}"), "000000").await;
+
+    assert!(result.is_err());
+
+    let session = api
+        .verify_phone_change(format!("+{phone}"), "123456")
+        .await?;
+
+    assert_eq!(session.user.phone, phone);
+
+    // Frees the number for the next run
+    get_service_api_client()
+        .delete_user(&session.user.id)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_check_health() -> Result<(), Box<dyn Error>> {
+    let health = get_api_client().health().await?;
+
+    assert_eq!(health.name, "GoTrue");
+    assert!(health.version.starts_with('v'));
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_get_settings() -> Result<(), Box<dyn Error>> {
+    let settings = Api::new("http://localhost:9999").get_settings().await?;
+
+    assert!(!settings.disable_signup);
+    assert!(!settings.mailer_autoconfirm);
+    assert!(!settings.phone_autoconfirm);
+    assert_eq!(settings.sms_provider, "twilio");
+    assert_eq!(settings.external.get("google"), Some(&true));
+
+    let settings = get_api_client().get_settings().await?;
+
+    assert!(settings.mailer_autoconfirm);
+    assert!(settings.phone_autoconfirm);
+    Ok(())
+}
+
+#[test]
+fn it_should_deserialize_users_with_unknown_factors() -> Result<(), Box<dyn Error>> {
+    let user: User = serde_json::from_value(json!({
+        "id": "user-id",
+        "email": "user@example.com",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": {},
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "factors": [{
+            "id": "factor-id",
+            "friendly_name": "Security key",
+            "factor_type": "webauthn",
+            "status": "pending",
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z"
+        }]
+    }))?;
+
+    assert_eq!(user.factors[0].factor_type, FactorType::Unknown);
+    assert_eq!(user.factors[0].status, FactorStatus::Unknown);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn it_should_list_users() -> Result<(), Box<dyn Error>> {
     let email = get_random_email();
     let password = "Abcd1234!";
     let client_api = get_api_client();
     client_api
-        .sign_up(EmailOrPhone::Email(email), password)
+        .sign_up(EmailOrPhone::Email(email), password, None)
         .await?;
 
     let api = get_service_api_client();
     let users = api.list_users(None).await?;
 
-    assert_eq!(users.users.is_empty(), false);
+    assert!(!users.users.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_list_users_by_page() -> Result<(), Box<dyn Error>> {
+    let password = "Abcd1234!";
+    let client_api = get_api_client();
+    for _ in 0..2 {
+        client_api
+            .sign_up(EmailOrPhone::Email(get_random_email()), password, None)
+            .await?;
+    }
+
+    let api = get_service_api_client();
+    let options = ListUsersOptions {
+        page: Some(1),
+        per_page: Some(1),
+    };
+    let users = api.list_users(Some(options)).await?;
+
+    assert_eq!(users.users.len(), 1);
+    assert!(users.total.unwrap() >= 2);
+    assert_eq!(users.next_page, Some(2));
+    assert!(users.last_page.unwrap() >= 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_list_all_users() -> Result<(), Box<dyn Error>> {
+    let address = start_user_list_server(vec![
+        (
+            vec!["1", "2"],
+            Some(r#"</admin/users?page=2&per_page=2>; rel="next", </admin/users?page=3&per_page=2>; rel="last""#),
+        ),
+        (
+            vec!["3", "4"],
+            Some(r#"</admin/users?page=3&per_page=2>; rel="next", </admin/users?page=3&per_page=2>; rel="last""#),
+        ),
+        (
+            vec!["5"],
+            Some(r#"</admin/users?page=3&per_page=2>; rel="last""#),
+        ),
+    ])
+    .await;
+    let api = Api::new(address);
+
+    let users: Vec<User> = api.list_all_users(Some(2)).try_collect().await?;
+    let ids: Vec<_> = users.iter().map(|user| user.id.as_str()).collect();
+    let unique: HashSet<_> = ids.iter().collect();
+
+    assert_eq!(ids, ["1", "2", "3", "4", "5"]);
+    assert_eq!(unique.len(), ids.len());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_list_all_users_without_next_link() -> Result<(), Box<dyn Error>> {
+    let address = start_user_list_server(vec![
+        (
+            vec!["1", "2"],
+            Some(r#"</admin/users?page=2&per_page=2>; rel="last""#),
+        ),
+        (vec!["3"], None),
+    ])
+    .await;
+    let api = Api::new(address);
+
+    let page = api.list_users(None).await?;
+
+    assert_eq!(page.next_page, None);
+    assert_eq!(page.last_page, Some(2));
+
+    let users: Vec<User> = api.list_all_users(Some(2)).try_collect().await?;
+
+    assert_eq!(users.len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_list_all_users_without_link_header() -> Result<(), Box<dyn Error>> {
+    let address = start_user_list_server(vec![(vec!["1", "2"], None), (vec!["3"], None)]).await;
+    let api = Api::new(address);
+
+    let page = api.list_users(None).await?;
+
+    assert_eq!(page.total, Some(3));
+    assert_eq!(page.next_page, None);
+    assert_eq!(page.last_page, None);
+
+    let users: Vec<User> = api.list_all_users(Some(2)).try_collect().await?;
+    let ids: Vec<_> = users.iter().map(|user| user.id.as_str()).collect();
+
+    assert_eq!(ids, ["1", "2", "3"]);
 
     Ok(())
 }
@@ -274,12 +918,12 @@ async fn it_should_get_user_by_id() -> Result<(), Box<dyn Error>> {
     let email = get_random_email();
     let password = "Abcd1234!";
     let client_api = get_api_client();
-    let session = client_api
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+    let outcome = client_api
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
 
     let api = get_service_api_client();
-    let user = api.get_user_by_id(&session.user.id).await?;
+    let user = api.get_user_by_id(&outcome.user().id).await?;
 
     assert_eq!(user.email, email);
 
@@ -290,13 +934,9 @@ async fn it_should_get_user_by_id() -> Result<(), Box<dyn Error>> {
 async fn it_should_create_user() -> Result<(), Box<dyn Error>> {
     let email = get_random_email();
     let api = get_service_api_client();
-    let user = AdminUserAttributes {
-        email: email.clone(),
-        password: Some(String::from("Abcd1234!")),
-        data: None,
-        email_confirmed: None,
-        phone_confirmed: None,
-    };
+    let user = AdminUserAttributes::new()
+        .email(&email)
+        .password("Abcd1234!");
 
     let response = api.create_user(user).await?;
 
@@ -309,32 +949,25 @@ async fn it_should_create_user() -> Result<(), Box<dyn Error>> {
 async fn it_should_update_user_by_id() -> Result<(), Box<dyn Error>> {
     let email = get_random_email();
     let api = get_service_api_client();
-    let user = AdminUserAttributes {
-        email: email.clone(),
-        password: Some(String::from("Abcd1234!")),
-        data: Some(serde_json::Value::Null),
-        email_confirmed: None,
-        phone_confirmed: None,
-    };
+    let user = AdminUserAttributes::new()
+        .email(&email)
+        .password("Abcd1234!");
 
     let create_response = api.create_user(user).await?;
     assert_eq!(create_response.email, email);
 
     let new_email = get_random_email();
 
-    let user = AdminUserAttributes {
-        email: new_email.clone(),
-        password: None,
-        data: None,
-        email_confirmed: None,
-        phone_confirmed: None,
-    };
+    let user = AdminUserAttributes::new()
+        .email(&new_email)
+        .email_confirm(true)
+        .app_metadata(json!({ "plan": "pro" }));
 
-    let update_response = api
-        .update_user_by_id(&create_response.id, user.clone())
-        .await?;
+    let update_response = api.update_user_by_id(&create_response.id, user).await?;
 
     assert_eq!(update_response.email, new_email);
+    assert!(update_response.email_confirmed_at.is_some());
+    assert_eq!(update_response.app_metadata["plan"], "pro");
 
     Ok(())
 }
@@ -343,13 +976,9 @@ async fn it_should_update_user_by_id() -> Result<(), Box<dyn Error>> {
 async fn it_should_delete_user() -> Result<(), Box<dyn Error>> {
     let email = get_random_email();
     let api = get_service_api_client();
-    let user = AdminUserAttributes {
-        email: email.clone(),
-        password: Some(String::from("Abcd1234!")),
-        data: Some(serde_json::Value::Null),
-        email_confirmed: None,
-        phone_confirmed: None,
-    };
+    let user = AdminUserAttributes::new()
+        .email(&email)
+        .password("Abcd1234!");
 
     let create_response = api.create_user(user).await?;
     assert_eq!(create_response.email, email);
@@ -357,14 +986,11 @@ async fn it_should_delete_user() -> Result<(), Box<dyn Error>> {
     let old_user_list = api.list_users(None).await?;
 
     api.delete_user(&create_response.id).await?;
-    assert_eq!(
-        old_user_list.users.iter().any(|user| user.email == email),
-        true
-    );
+    assert!(old_user_list.users.iter().any(|user| user.email == email));
 
     let userlist = api.list_users(None).await?;
 
-    assert_eq!(userlist.users.iter().any(|user| user.email == email), false);
+    assert!(!userlist.users.iter().any(|user| user.email == email));
 
     Ok(())
 }
@@ -1,10 +1,39 @@
-use go_true::{Client, EmailOrPhone, UserAttributes};
+use go_true::{
+    mfa::{AssuranceLevel, EnrollParams},
+    Api, Client, DomainOrProviderId, EmailChangeOptions, EmailChangeOutcome, EmailOrPhone,
+    EmailOtpType, IdTokenCredentials, PendingConfirmations, Provider, SignOutScope, UserAttributes,
+    VerifyOtpParams,
+};
+use hmac::{Hmac, Mac};
+use jwt::SignWithKey;
 use rand::{distributions::Alphanumeric, Rng};
-use serde_json::json;
-use std::error::Error;
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 fn get_client() -> Client {
-    return Client::new("http://localhost:9998");
+    Client::new("http://localhost:9998")
+}
+
+fn get_service_api() -> Api {
+    let key: Hmac<Sha256> = Hmac::new_from_slice(b"37c304f8-51aa-419a-a1af-06154e63707a").unwrap();
+    let mut claims = BTreeMap::new();
+    claims.insert("sub", "1234567890");
+    claims.insert("role", "supabase_admin");
+
+    let token_str = claims.sign_with_key(&key).unwrap();
+    Api::new("http://localhost:9998").insert_header("Authorization", format!("Bearer {token_str}"))
+}
+
+/// A client for the server without autoconfirm, where email and phone changes have to be
+/// confirmed.
+fn get_confirming_client() -> Client {
+    Client::new("http://localhost:9999")
 }
 
 fn get_random_email() -> String {
@@ -15,7 +44,78 @@ fn get_random_email() -> String {
         .map(char::from)
         .collect();
 
-    return format!("{random_string}@example.com");
+    format!("{random_string}@example.com")
+}
+
+/// Reads the `nth` email sent to `email`, counting from 1.
+async fn get_email(email: &str, nth: usize) -> Result<String, Box<dyn Error>> {
+    let mailbox = format!(
+        "http://localhost:9000/api/v1/mailbox/{}",
+        email.split('@').next().unwrap()
+    );
+
+    for _ in 0..20 {
+        let messages: Vec<Value> = reqwest::get(&mailbox).await?.json().await?;
+
+        if let Some(id) = messages
+            .get(nth - 1)
+            .and_then(|message| message["id"].as_str())
+        {
+            return Ok(reqwest::get(format!("{mailbox}/{id}"))
+                .await?
+                .text()
+                .await?);
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    Err(format!("No email sent to {email}").into())
+}
+
+/// Reads the token hash from the confirmation link of the `nth` email sent to `email`.
+async fn get_email_token_hash(email: &str, nth: usize) -> Result<String, Box<dyn Error>> {
+    let message = get_email(email, nth).await?;
+    let (_, link) = message.split_once("token=").ok_or("No link in email")?;
+
+    Ok(link
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect())
+}
+
+/// Reads the OTP code of the `nth` email sent to `email`.
+async fn get_email_otp(email: &str, nth: usize) -> Result<String, Box<dyn Error>> {
+    let message = get_email(email, nth).await?;
+    let (_, code) = message
+        .split_once("enter the code: ")
+        .ok_or("No code in email")?;
+
+    Ok(code.chars().take_while(char::is_ascii_digit).collect())
+}
+
+/// Computes the current TOTP code for a base32 encoded secret.
+fn get_totp_code(secret: &str) -> String {
+    let secret = base32::decode(base32::Alphabet::RFC4648 { padding: false }, secret).unwrap();
+    let counter = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        / 30;
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(&secret).unwrap();
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[19] & 0xf) as usize;
+    let code = u32::from_be_bytes([
+        hash[offset],
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]) & 0x7fff_ffff;
+
+    format!("{:06}", code % 1_000_000)
 }
 
 #[tokio::test]
@@ -25,10 +125,11 @@ async fn it_signs_up_with_email() -> Result<(), Box<dyn Error>> {
 
     let mut client = get_client();
     let res = client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
 
-    assert_eq!(email, res.user.email);
+    assert_eq!(email, res.user().email);
+    assert!(res.session().is_some());
 
     Ok(())
 }
@@ -40,10 +141,12 @@ async fn it_should_throw_email_already_taken_error() -> Result<(), Box<dyn Error
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
 
-    let result = client.sign_up(EmailOrPhone::Email(email), password).await;
+    let result = client
+        .sign_up(EmailOrPhone::Email(email), password, None)
+        .await;
 
     match result {
         Ok(_) => panic!("Should throw error"),
@@ -60,7 +163,7 @@ async fn it_signs_in_with_email() -> Result<(), Box<dyn Error>> {
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let res = client
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -77,7 +180,9 @@ async fn it_should_return_error_when_credentials_are_wrong_on_signin() -> Result
     let password = "Abcd1234!";
 
     let mut client = get_client();
-    client.sign_up(EmailOrPhone::Email(email), password).await?;
+    client
+        .sign_up(EmailOrPhone::Email(email), password, None)
+        .await?;
 
     let wrong_email = get_random_email();
     let result = client
@@ -112,7 +217,7 @@ async fn it_should_refresh_session() -> Result<(), Box<dyn Error>> {
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let old_session = client
         .sign_in(EmailOrPhone::Email(email.clone()), password)
@@ -133,11 +238,11 @@ async fn it_send_magic_link_with_valid_email() -> Result<(), Box<dyn Error>> {
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     let res = client.send_otp(EmailOrPhone::Email(email), None).await?;
 
-    assert_eq!(res, true);
+    assert!(res);
     Ok(())
 }
 
@@ -149,7 +254,47 @@ async fn it_does_not_send_magic_link_with_invalid_email() -> Result<(), Box<dyn
 
     match result {
         Ok(_) => panic!("Should throw error"),
-        Err(e) => assert!(matches!(e, go_true::error::Error::UserNotFound)),
+        Err(e) => assert!(matches!(e, go_true::error::Error::InvalidInput)),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_does_not_send_magic_link_to_unknown_user() -> Result<(), Box<dyn Error>> {
+    let client = get_client();
+    let result = client
+        .send_otp(EmailOrPhone::Email(get_random_email()), Some(false))
+        .await;
+
+    assert!(matches!(result, Err(go_true::error::Error::UserNotFound)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_when_otp_is_wrong() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    client
+        .send_otp(EmailOrPhone::Email(email.clone()), None)
+        .await?;
+
+    let params = VerifyOtpParams::Email {
+        email,
+        token: "000000".to_string(),
+        otp_type: EmailOtpType::Magiclink,
+    };
+    let result = client.verify_otp(params).await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(e) => assert!(matches!(e, go_true::error::Error::WrongToken)),
     }
 
     Ok(())
@@ -162,20 +307,70 @@ async fn it_should_log_out() -> Result<(), Box<dyn Error>> {
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     client.sign_in(EmailOrPhone::Email(email), password).await?;
 
-    let success = client.sign_out().await?;
+    let success = client.sign_out(None).await?;
 
-    assert_eq!(success, true);
+    assert!(success);
+
+    let result = client.sign_out(None).await;
+    assert!(matches!(
+        result,
+        Err(go_true::error::Error::NotAuthenticated)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_log_out_with_revoked_token() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = client.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    Api::new("http://localhost:9998")
+        .sign_out(&session.access_token, None)
+        .await?;
+
+    assert!(client.sign_out(None).await?);
+
+    let result = client.sign_out(None).await;
+    assert!(matches!(
+        result,
+        Err(go_true::error::Error::NotAuthenticated)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_keep_session_when_logging_out_others() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    client.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    client.sign_out(Some(SignOutScope::Others)).await?;
+
+    assert!(client.refresh_session().await.is_ok());
     Ok(())
 }
 
 #[tokio::test]
 async fn it_should_return_error_in_log_out_if_no_session() -> Result<(), Box<dyn Error>> {
-    let client = get_client();
-    let result = client.sign_out().await;
+    let mut client = get_client();
+    let result = client.sign_out(None).await;
 
     match result {
         Ok(_) => panic!("Should throw error"),
@@ -192,11 +387,239 @@ async fn it_should_send_password_recovery_email() -> Result<(), Box<dyn Error>>
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let res = client.reset_password_for_email(&email, None).await?;
+
+    assert!(res);
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_not_reveal_unknown_users_on_password_recovery() -> Result<(), Box<dyn Error>> {
+    let client = get_client();
+
+    let res = client
+        .reset_password_for_email(get_random_email(), None)
         .await?;
-    let res = client.reset_password_for_email(&email).await?;
+    assert!(res);
+
+    let result = client.reset_password_for_email("not-an-email", None).await;
+    assert!(matches!(result, Err(go_true::error::Error::InvalidInput)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_recover_password() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    client.reset_password_for_email(&email, None).await?;
+
+    let params = VerifyOtpParams::TokenHash {
+        token_hash: get_email_token_hash(&email, 1).await?,
+        otp_type: EmailOtpType::Recovery,
+    };
+    client.verify_otp(params).await?;
+
+    assert!(client.is_password_recovery());
+
+    client.complete_password_recovery("Abcd12345!").await?;
+
+    assert!(!client.is_password_recovery());
+
+    client.sign_out(None).await?;
+    client
+        .sign_in(EmailOrPhone::Email(email), "Abcd12345!")
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_not_complete_password_recovery_without_recovery_session(
+) -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+    assert!(!client.is_password_recovery());
+
+    let result = client.complete_password_recovery("Abcd12345!").await;
+
+    assert!(matches!(
+        result,
+        Err(go_true::error::Error::NoPasswordRecovery)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_request_email_change() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    get_client()
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let mut client = get_confirming_client();
+    client.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let new_email = get_random_email();
+    let request = client.request_email_change(&new_email, None).await?;
+
+    assert_eq!(request.user.new_email, Some(new_email.clone()));
+    assert_eq!(request.pending, PendingConfirmations::Unknown);
+
+    let result = client.verify_email_change(new_email, "000000").await;
+
+    assert!(matches!(result, Err(go_true::error::Error::WrongToken)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_change_email_after_both_confirmations() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    get_client()
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let mut client = get_confirming_client();
+    client
+        .sign_in(EmailOrPhone::Email(email.clone()), password)
+        .await?;
+
+    let new_email = get_random_email();
+    let options = EmailChangeOptions {
+        secure_email_change: Some(true),
+        ..Default::default()
+    };
+    let request = client
+        .request_email_change(&new_email, Some(options))
+        .await?;
+
+    assert_eq!(request.pending, PendingConfirmations::BothAddresses);
+
+    let code = get_email_otp(&new_email, 1).await?;
+    let outcome = client.verify_email_change(&new_email, code).await?;
+
+    assert!(matches!(
+        outcome,
+        EmailChangeOutcome::OtherAddressPending { .. }
+    ));
+
+    let code = get_email_otp(&email, 1).await?;
+    let outcome = client.verify_email_change(&email, code).await?;
+
+    assert_eq!(outcome.session().unwrap().user.email, new_email);
+    assert_eq!(client.refresh_session().await?.user.email, new_email);
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_change_phone() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    get_client()
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    // The number has a fixed OTP on the server without SMS autoconfirm
+    let phone = "4915112345680";
+    let mut client = get_confirming_client();
+    client.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let update = client
+        .request_phone_change(format!("+{phone}"), None)
+        .await?;
+
+    assert_eq!(update.new_phone.as_deref(), Some(phone));
+
+    let result = client
+        .verify_phone_change(format!("+{phone}"), "000000")
+        .await;
+
+    assert!(matches!(result, Err(go_true::error::Error::WrongToken)));
+
+    let session = client
+        .verify_phone_change(format!("+{phone}"), "123456")
+        .await?;
+
+    assert_eq!(session.user.phone, phone);
+
+    // Frees the number for the next run
+    get_service_api().delete_user(&session.user.id).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_remember_server_version() -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+    assert_eq!(client.server_version(), None);
+
+    let health = client.health().await?;
+
+    assert_eq!(client.server_version(), Some(health.version.as_str()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_refuse_mfa_on_old_server_versions() -> Result<(), Box<dyn Error>> {
+    for version in ["v2.39.9", "2.30.0", "v1.99.0-rc.1"] {
+        let mut client = get_client();
+        client.set_server_version(version);
+
+        let result = client.mfa_list_factors().await;
+
+        match result {
+            Err(go_true::error::Error::UnsupportedServerVersion { version: v, .. }) => {
+                assert_eq!(v, version)
+            }
+            _ => panic!("{version} should not support MFA"),
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_allow_mfa_on_new_or_unknown_server_versions() -> Result<(), Box<dyn Error>> {
+    for version in [
+        "v2.40.0",
+        "2.164.0",
+        "v2.41.0-rc.1",
+        "v3.0.0+build",
+        "unspecified",
+    ] {
+        let mut client = get_client();
+        client.set_server_version(version);
+
+        let result = client.mfa_list_factors().await;
+
+        assert!(
+            matches!(result, Err(go_true::error::Error::NotAuthenticated)),
+            "{version} should support MFA"
+        );
+    }
 
-    assert_eq!(res, true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_cache_settings() -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+
+    let settings = client.get_settings().await?;
+    let cached = client.get_settings().await?;
+
+    assert_eq!(settings.mailer_autoconfirm, cached.mailer_autoconfirm);
+    assert_eq!(settings.external, cached.external);
     Ok(())
 }
 
@@ -207,20 +630,308 @@ async fn it_should_update_user() -> Result<(), Box<dyn Error>> {
 
     let mut client = get_client();
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
         .await?;
     client.sign_in(EmailOrPhone::Email(email), password).await?;
 
     let new_email = get_random_email();
     let attributes = UserAttributes {
-        email: new_email.clone(),
-        password: "Abcd12345!".to_string(),
-        data: json!({ "test": "test" }),
+        email: Some(new_email.clone()),
+        password: Some("Abcd12345!".to_string()),
+        data: Some(json!({ "test": "test" })),
+        ..Default::default()
+    };
+
+    let update = client.update_user(attributes).await?;
+
+    assert_eq!(update.new_email, Some(new_email));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_in_reauthenticate_if_no_session() -> Result<(), Box<dyn Error>> {
+    let client = get_client();
+    let result = client.reauthenticate().await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(e) => assert!(matches!(e, go_true::error::Error::NotAuthenticated)),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_sign_in_anonymously_and_convert_user() -> Result<(), Box<dyn Error>> {
+    let mut client = get_confirming_client();
+    let session = client.sign_in_anonymously(None).await?;
+
+    assert!(session.user.is_anonymous);
+
+    let email = get_random_email();
+    let attributes = UserAttributes {
+        email: Some(email.clone()),
+        ..Default::default()
     };
 
     let update = client.update_user(attributes).await?;
 
-    assert_eq!(update.new_email, new_email);
+    assert_eq!(update.id, session.user.id);
+    assert_eq!(update.new_email, Some(email.clone()));
+    assert!(client.refresh_session().await?.user.is_anonymous);
+
+    let params = VerifyOtpParams::TokenHash {
+        token_hash: get_email_token_hash(&email, 1).await?,
+        otp_type: EmailOtpType::EmailChange,
+    };
+    let session = client.verify_otp(params).await?;
+
+    assert_eq!(session.user.email, email);
+    assert!(!session.user.is_anonymous);
+
+    let attributes = UserAttributes {
+        password: Some("Abcd1234!".to_string()),
+        ..Default::default()
+    };
+
+    client.update_user(attributes).await?;
+    client.sign_out(None).await?;
+    client
+        .sign_in(EmailOrPhone::Email(email), "Abcd1234!")
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_when_id_token_is_invalid() -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+    let credentials = IdTokenCredentials {
+        provider: Provider::Google,
+        id_token: "invalid-id-token".to_string(),
+        access_token: None,
+        nonce: None,
+    };
+
+    let result = client.sign_in_with_id_token(credentials).await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(e) => assert!(matches!(e, go_true::error::Error::WrongToken)),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_when_sso_domain_is_unknown() -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+    assert!(client.get_settings().await?.saml_enabled);
+
+    let domain = DomainOrProviderId::Domain("unknown.example.com".to_string());
+
+    let result = client.sign_in_with_sso(domain, None).await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(e) => assert!(matches!(e, go_true::error::Error::SsoProviderNotFound)),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_when_mfa_code_is_wrong() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let params = EnrollParams::Totp {
+        friendly_name: None,
+        issuer: None,
+    };
+    let factor = client.mfa_enroll(params).await?;
+    let challenge = client.mfa_challenge(&factor.id, None).await?;
+    let result = client.mfa_verify(&factor.id, &challenge.id, "000000").await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(e) => assert!(matches!(e, go_true::error::Error::WrongToken)),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_upgrade_assurance_level_after_mfa_verify() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    client.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let params = EnrollParams::Totp {
+        friendly_name: None,
+        issuer: None,
+    };
+    let factor = client.mfa_enroll(params).await?;
+    let secret = factor.totp.unwrap().secret;
+
+    let aal = client.get_authenticator_assurance_level()?;
+    assert_eq!(aal.current_level, Some(AssuranceLevel::Aal1));
+
+    let challenge = client.mfa_challenge(&factor.id, None).await?;
+    client
+        .mfa_verify(&factor.id, &challenge.id, get_totp_code(&secret))
+        .await?;
+
+    let aal = client.get_authenticator_assurance_level()?;
+
+    assert_eq!(aal.current_level, Some(AssuranceLevel::Aal2));
+    assert_eq!(aal.next_level, Some(AssuranceLevel::Aal2));
+    assert!(aal
+        .current_authentication_methods
+        .iter()
+        .any(|method| method.method == "totp"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_authenticator_assurance_level() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    client.sign_in(EmailOrPhone::Email(email), password).await?;
+
+    let aal = client.get_authenticator_assurance_level()?;
+
+    assert_eq!(aal.current_level, Some(AssuranceLevel::Aal1));
+    assert_eq!(aal.next_level, Some(AssuranceLevel::Aal1));
+    assert!(aal
+        .current_authentication_methods
+        .iter()
+        .any(|amr| amr.method == "password"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_get_user_identities() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let identities = client.get_user_identities().await?;
+
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0].provider, "email");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_when_unlinking_only_identity() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+
+    let identities = client.get_user_identities().await?;
+    let result = client.unlink_identity(&identities[0].identity_id).await;
+
+    assert!(result.is_err(), "Should throw error");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_get_session_from_url() -> Result<(), Box<dyn Error>> {
+    let email = get_random_email();
+    let password = "Abcd1234!";
+
+    let mut client = get_client();
+    client
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?;
+    let session = client
+        .sign_in(EmailOrPhone::Email(email.clone()), password)
+        .await?;
+
+    let url = format!(
+        "http://localhost:3000/callback#access_token={}&refresh_token={}\
+         &expires_in=3600&token_type=bearer&provider_token=provider-token",
+        session.access_token, session.refresh_token
+    );
+    let parsed = client.get_session_from_url(url).await?;
+
+    assert_eq!(parsed.user.email, email);
+    assert_eq!(parsed.provider_token.as_deref(), Some("provider-token"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_from_callback_url() -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+    let url = "http://localhost:3000/callback#error=access_denied\
+               &error_code=403&error_description=Email+link+is+invalid";
+
+    let result = client.get_session_from_url(url).await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(go_true::error::Error::CallbackError {
+            error,
+            error_code,
+            description,
+        }) => {
+            assert_eq!(error, "access_denied");
+            assert_eq!(error_code.as_deref(), Some("403"));
+            assert_eq!(description.as_deref(), Some("Email link is invalid"));
+        }
+        Err(e) => panic!("Unexpected error {e}"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_should_return_error_without_description_from_callback_url() -> Result<(), Box<dyn Error>>
+{
+    let mut client = get_client();
+    let url = "http://localhost:3000/callback#error=server_error";
+
+    let result = client.get_session_from_url(url).await;
+
+    match result {
+        Err(go_true::error::Error::CallbackError {
+            error, description, ..
+        }) => {
+            assert_eq!(error, "server_error");
+            assert_eq!(description, None);
+        }
+        _ => panic!("expected a callback error"),
+    }
 
     Ok(())
 }
@@ -232,11 +943,29 @@ async fn it_should_set_session_by_refresh_token() -> Result<(), Box<dyn Error>>
 
     let mut client = get_client();
     let old_session = client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
-        .await?;
+        .sign_up(EmailOrPhone::Email(email.clone()), password, None)
+        .await?
+        .session()
+        .cloned()
+        .expect("Should return a session");
 
     let session = client.set_session(&old_session.refresh_token).await?;
     assert_eq!(old_session.user.email, session.user.email);
 
     Ok(())
 }
+
+#[tokio::test]
+async fn it_should_return_error_when_exchanging_invalid_auth_code() -> Result<(), Box<dyn Error>> {
+    let mut client = get_client();
+    let result = client
+        .exchange_code_for_session("invalid-auth-code", "invalid-code-verifier")
+        .await;
+
+    match result {
+        Ok(_) => panic!("Should throw error"),
+        Err(e) => assert!(matches!(e, go_true::error::Error::WrongToken)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use go_true::{error::Error, Client, LoopbackListener, Provider};
+use serde_json::json;
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use url::Url;
+
+/// Starts a stand-in GoTrue server that redirects `/authorize` back to `redirect_to` with
+/// the given query and answers the PKCE token exchange with a session.
+async fn start_server(callback_query: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 4096];
+            let read = stream.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let url = Url::parse(&format!("http://localhost{path}")).unwrap();
+
+            let response = if url.path() == "/authorize" {
+                let (_, redirect_to) = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "redirect_to")
+                    .unwrap();
+                format!(
+                    "HTTP/1.1 302 Found\r\nLocation: {redirect_to}?{callback_query}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+            } else {
+                let body = json!({
+                    "access_token": "access-token",
+                    "token_type": "bearer",
+                    "expires_in": 3600,
+                    "expires_at": 1700000000,
+                    "refresh_token": "refresh-token",
+                    "user": {
+                        "id": "user-id",
+                        "email": "user@example.com",
+                        "aud": "authenticated",
+                        "role": "authenticated",
+                        "phone": "",
+                        "app_metadata": {},
+                        "user_metadata": {},
+                        "created_at": "2024-01-01T00:00:00Z",
+                        "updated_at": "2024-01-01T00:00:00Z"
+                    }
+                })
+                .to_string();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            };
+
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+
+    address
+}
+
+#[tokio::test]
+async fn it_signs_in_with_loopback() -> Result<(), Box<dyn std::error::Error>> {
+    let address = start_server("code=test-code").await;
+    let mut client = Client::new(&address);
+
+    let session = client
+        .sign_in_with_loopback(Provider::Github, None, |url| {
+            tokio::spawn(reqwest::get(url.to_string()));
+        })
+        .await?;
+
+    assert_eq!(session.access_token, "access-token");
+    assert_eq!(session.user.email, "user@example.com");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_returns_callback_errors_from_loopback() -> Result<(), Box<dyn std::error::Error>> {
+    let address = start_server("error=access_denied&error_description=User+denied").await;
+    let mut client = Client::new(&address);
+
+    let result = client
+        .sign_in_with_loopback(Provider::Github, None, |url| {
+            tokio::spawn(reqwest::get(url.to_string()));
+        })
+        .await;
+
+    match result {
+        Err(Error::CallbackError {
+            error, description, ..
+        }) => {
+            assert_eq!(error, "access_denied");
+            assert_eq!(description.as_deref(), Some("User denied"));
+        }
+        _ => panic!("expected a callback error"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_returns_callback_errors_without_description_from_loopback(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let address = start_server("error=access_denied").await;
+    let mut client = Client::new(&address);
+
+    let result = client
+        .sign_in_with_loopback(Provider::Github, None, |url| {
+            tokio::spawn(reqwest::get(url.to_string()));
+        })
+        .await;
+
+    match result {
+        Err(Error::CallbackError {
+            error, description, ..
+        }) => {
+            assert_eq!(error, "access_denied");
+            assert_eq!(description, None);
+        }
+        _ => panic!("expected a callback error"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_ignores_other_paths_on_loopback() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = LoopbackListener::bind(0).await?;
+    let callback = listener.redirect_url().to_string();
+    let base = callback.trim_end_matches("/callback").to_string();
+
+    tokio::spawn(async move {
+        let favicon = reqwest::get(format!("{base}/favicon.ico")).await.unwrap();
+        assert_eq!(favicon.status(), 404);
+        reqwest::get(format!("{callback}?code=abc")).await.unwrap();
+    });
+
+    assert_eq!(listener.wait_for_code().await?, "abc");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_is_not_blocked_by_idle_connections_on_loopback(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = LoopbackListener::bind(0).await?;
+    let callback = listener.redirect_url().to_string();
+    let address = callback
+        .trim_start_matches("http://")
+        .trim_end_matches("/callback")
+        .to_string();
+
+    let idle = TcpStream::connect(&address).await?;
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        reqwest::get(format!("{callback}?code=abc")).await.unwrap();
+    });
+
+    let code = tokio::time::timeout(Duration::from_secs(5), listener.wait_for_code()).await??;
+
+    assert_eq!(code, "abc");
+    drop(idle);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_times_out_without_redirect_on_loopback() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = LoopbackListener::bind(0)
+        .await?
+        .timeout(Duration::from_millis(200));
+
+    let result = listener.wait_for_code().await;
+
+    assert!(matches!(result, Err(Error::RedirectTimeout)));
+    Ok(())
+}